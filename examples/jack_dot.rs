@@ -0,0 +1,10 @@
+//! Prints the JACK graph in the Graphviz DOT language.
+//!
+//! Usage:
+//! * `cargo run --example jack_dot > jack.dot`
+//! * `cargo run --example jack_dot | dot -Tsvg > jack.svg`
+fn main() {
+    let (client, _status) =
+        jack::Client::new("rust_jack_dot", jack::ClientOptions::NO_START_SERVER).unwrap();
+    print!("{}", client.graph().to_dot());
+}
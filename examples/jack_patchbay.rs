@@ -0,0 +1,76 @@
+//! Saves, restores and compares the connections between JACK ports.
+//!
+//! Usage:
+//! * `cargo run --example jack_patchbay -- save studio.json [CLIENT_REGEX]`
+//! * `cargo run --example jack_patchbay -- restore studio.json`
+//! * `cargo run --example jack_patchbay -- diff studio.json [CLIENT_REGEX]`
+use jack::contrib::patchbay::Patchbay;
+use std::fs::File;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, path, client_pattern) = match args.as_slice() {
+        [command, path] => (command.as_str(), path, None),
+        [command, path, pattern] => (command.as_str(), path, Some(pattern.as_str())),
+        _ => usage(),
+    };
+
+    let (client, _status) =
+        jack::Client::new("rust_jack_patchbay", jack::ClientOptions::NO_START_SERVER).unwrap();
+    match command {
+        "save" => {
+            let patchbay = Patchbay::capture(&client, client_pattern);
+            patchbay.write(File::create(path).unwrap()).unwrap();
+            println!("Saved {} connections to {path}", patchbay.connections.len());
+        }
+        "restore" => {
+            let report = read(path).restore(&client);
+            for connection in &report.connected {
+                println!("connected {} -> {}", connection.output, connection.input);
+            }
+            for port in &report.missing_ports {
+                eprintln!("missing port {port}");
+            }
+            for (connection, err) in &report.failed {
+                eprintln!(
+                    "failed to connect {} -> {}: {err}",
+                    connection.output, connection.input
+                );
+            }
+            println!(
+                "{} connected, {} already connected",
+                report.connected.len(),
+                report.already_connected.len()
+            );
+            if !report.is_complete() {
+                process::exit(1);
+            }
+        }
+        "diff" => {
+            let saved = read(path);
+            let current = Patchbay::capture(&client, client_pattern);
+            let diff = Patchbay::diff(&saved, &current);
+            for connection in &diff.removed {
+                println!("- {} -> {}", connection.output, connection.input);
+            }
+            for connection in &diff.added {
+                println!("+ {} -> {}", connection.output, connection.input);
+            }
+        }
+        _ => usage(),
+    }
+}
+
+fn read(path: &str) -> Patchbay {
+    let file = File::open(path).unwrap();
+    Patchbay::read(file).unwrap_or_else(|err| {
+        eprintln!("Failed to read {path}: {err}");
+        process::exit(1);
+    })
+}
+
+fn usage() -> ! {
+    eprintln!("usage: jack_patchbay save|restore|diff FILE [CLIENT_REGEX]");
+    process::exit(2);
+}
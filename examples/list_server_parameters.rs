@@ -0,0 +1,37 @@
+//! Lists the parameters of an in-process JACK server, its drivers and its internal clients along
+//! with their descriptions, defaults and constraints.
+fn print_parameters(parameters: Vec<jack::server::Parameter>) {
+    for parameter in parameters {
+        println!(
+            "  -{} {}: {} (default: {})",
+            parameter.id().unwrap(),
+            parameter.name().unwrap(),
+            parameter.short_description().unwrap(),
+            parameter.default_value().unwrap(),
+        );
+        if let Some(constraint) = parameter.constraint().unwrap() {
+            println!("      {constraint:?}");
+        }
+    }
+}
+
+fn main() {
+    let server = jack::server::Server::new().unwrap();
+
+    println!("server");
+    print_parameters(server.parameters().unwrap());
+
+    for driver in server.drivers().unwrap() {
+        println!(
+            "driver {} ({:?})",
+            driver.name().unwrap(),
+            driver.driver_type().unwrap()
+        );
+        print_parameters(driver.parameters().unwrap());
+    }
+
+    for internal in server.internals().unwrap() {
+        println!("internal {}", internal.name().unwrap());
+        print_parameters(internal.parameters().unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 //! Creates a jack midi input and output ports. The application prints
-//! out all values sent to it through the input port. It also sends a
-//! Note On and Off event, once every cycle, on the output port.
-use std::convert::From;
+//! out all values sent to it through the input port, decoded as a `MidiMessage` when possible. It
+//! also sends a Note On and Off event, once every cycle, on the output port.
+use std::convert::{From, TryFrom};
 use std::io;
 use std::sync::mpsc::sync_channel;
 
@@ -30,13 +30,15 @@ impl From<jack::RawMidi<'_>> for MidiCopy {
 
 impl std::fmt::Debug for MidiCopy {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "Midi {{ time: {}, len: {}, data: {:?} }}",
-            self.time,
-            self.len,
-            &self.data[..self.len]
-        )
+        let data = &self.data[..self.len];
+        match jack::MidiMessage::try_from(data) {
+            Ok(message) => write!(f, "Midi {{ time: {}, message: {:?} }}", self.time, message),
+            Err(_) => write!(
+                f,
+                "Midi {{ time: {}, len: {}, data: {:?} }}",
+                self.time, self.len, data
+            ),
+        }
     }
 }
 
@@ -65,24 +67,24 @@ fn main() {
         }
         let mut put_p = maker.writer(ps);
         put_p
-            .write(&jack::RawMidi {
-                time: 0,
-                bytes: &[
-                    0b10010000, /* Note On, channel 1 */
-                    0b01000000, /* Key number */
-                    0b01111111, /* Velocity */
-                ],
-            })
+            .write_message(
+                0,
+                &jack::MidiMessage::NoteOn {
+                    channel: 0,
+                    note: 64,
+                    velocity: 127,
+                },
+            )
             .unwrap();
         put_p
-            .write(&jack::RawMidi {
-                time: ps.n_frames() / 2,
-                bytes: &[
-                    0b10000000, /* Note Off, channel 1 */
-                    0b01000000, /* Key number */
-                    0b01111111, /* Velocity */
-                ],
-            })
+            .write_message(
+                ps.n_frames() / 2,
+                &jack::MidiMessage::NoteOff {
+                    channel: 0,
+                    note: 64,
+                    velocity: 127,
+                },
+            )
             .unwrap();
         jack::Control::Continue
     };
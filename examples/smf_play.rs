@@ -0,0 +1,44 @@
+//! Plays a Standard MIDI File on a midi output port while the JACK transport is rolling.
+//!
+//! Usage: `cargo run --example smf_play -- song.mid`
+use jack::contrib::smf::{play::SmfPlayer, Smf};
+use std::io;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("expected the path of a midi file");
+    let file = std::fs::File::open(&path).unwrap();
+    let smf = Smf::read(io::BufReader::new(file)).unwrap();
+
+    // Open the client.
+    let (client, _status) =
+        jack::Client::new("rust_jack_smf_play", jack::ClientOptions::default()).unwrap();
+    let mut port = client
+        .register_port("midi_out", jack::MidiOut::default())
+        .unwrap();
+
+    // Play the events that fall into each cycle.
+    let mut player = SmfPlayer::new(&smf);
+    let cback = move |client: &jack::Client, ps: &jack::ProcessScope| -> jack::Control {
+        let mut writer = port.writer(ps);
+        if let Err(err) = player.play(client, ps, &mut writer) {
+            eprintln!("Failed to play events: {err}");
+            return jack::Control::Quit;
+        }
+        jack::Control::Continue
+    };
+    let active_client = client
+        .activate_async((), jack::contrib::ClosureProcessHandler::new(cback))
+        .unwrap();
+
+    // Wait
+    println!("Playing {path} while the transport is rolling, press any key to quit");
+    let mut user_input = String::new();
+    io::stdin().read_line(&mut user_input).ok();
+
+    // Optional deactivation.
+    if let Err(err) = active_client.deactivate() {
+        eprintln!("JACK exited with error: {err}");
+    };
+}
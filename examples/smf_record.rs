@@ -0,0 +1,63 @@
+//! Records the midi input port to a Standard MIDI File until a key is pressed.
+//!
+//! Usage: `cargo run --example smf_record -- recording.mid`
+use jack::contrib::smf::{record, SmfFormat, DEFAULT_TEMPO};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "recording.mid".to_string());
+
+    // Open the client.
+    let (client, _status) =
+        jack::Client::new("rust_jack_smf_record", jack::ClientOptions::default()).unwrap();
+    let port = client
+        .register_port("midi_in", jack::MidiIn::default())
+        .unwrap();
+
+    // Copy events out of the real-time thread through a ring buffer.
+    let (mut recorder, mut input) = record::channel(1 << 16).unwrap();
+    let cback = move |_: &jack::Client, ps: &jack::ProcessScope| -> jack::Control {
+        input.record(ps, &port);
+        jack::Control::Continue
+    };
+    let active_client = client
+        .activate_async((), jack::contrib::ClosureProcessHandler::new(cback))
+        .unwrap();
+    let sample_rate = active_client.as_client().sample_rate();
+
+    // Collect events in a non-real-time thread until the user presses a key.
+    let stop = Arc::new(AtomicBool::new(false));
+    let collector = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(50));
+                recorder.collect();
+            }
+            recorder.collect();
+            recorder
+        })
+    };
+    println!("Recording to {path}, press any key to stop");
+    let mut user_input = String::new();
+    io::stdin().read_line(&mut user_input).ok();
+
+    if let Err(err) = active_client.deactivate() {
+        eprintln!("JACK exited with error: {err}");
+    };
+    stop.store(true, Ordering::Relaxed);
+    let recorder = collector.join().unwrap();
+    if recorder.dropped_events() > 0 {
+        eprintln!("Dropped {} events", recorder.dropped_events());
+    }
+
+    let smf = recorder.to_smf(SmfFormat::SingleTrack, sample_rate, 960, DEFAULT_TEMPO);
+    let file = std::fs::File::create(&path).unwrap();
+    smf.write(io::BufWriter::new(file)).unwrap();
+    println!("Wrote {} events to {path}", recorder.len());
+}
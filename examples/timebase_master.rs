@@ -0,0 +1,66 @@
+//! Becomes the JACK timebase master and publishes bar, beat and tick information for a fixed tempo
+//! and time signature. Run `show_transport` alongside to see the published position.
+use std::io;
+
+struct Sequencer {
+    bpm: f64,
+    sig_num: f32,
+    sig_denom: f32,
+    ticks_per_beat: f64,
+}
+
+impl jack::TimebaseHandler for Sequencer {
+    fn timebase(
+        &mut self,
+        _: &jack::Client,
+        _state: jack::TransportState,
+        _n_frames: jack::Frames,
+        pos: &mut jack::TransportPosition,
+        _is_new_position: bool,
+    ) {
+        let Some(frame_rate) = pos.frame_rate() else {
+            return;
+        };
+        // Compute the position from scratch on every cycle. Simple but correct for a fixed tempo.
+        let beats = pos.frame() as f64 / frame_rate as f64 * self.bpm / 60.0;
+        let beats_per_bar = self.sig_num as f64;
+        let bar = (beats / beats_per_bar).floor();
+        let beat = (beats - bar * beats_per_bar).floor();
+        let tick = ((beats - beats.floor()) * self.ticks_per_beat).floor();
+        let bbt = jack::TransportBBT {
+            bar: bar as usize + 1,
+            beat: beat as usize + 1,
+            tick: tick as usize,
+            sig_num: self.sig_num,
+            sig_denom: self.sig_denom,
+            ticks_per_beat: self.ticks_per_beat,
+            bpm: self.bpm,
+            bar_start_tick: bar * beats_per_bar * self.ticks_per_beat,
+        };
+        pos.set_bbt(Some(bbt)).unwrap();
+    }
+}
+
+fn main() {
+    let (client, _status) =
+        jack::Client::new("rust_jack_timebase", jack::ClientOptions::default()).unwrap();
+    let mut active_client = client.activate_async((), ()).unwrap();
+    active_client
+        .set_timebase_handler(Sequencer {
+            bpm: 120.0,
+            sig_num: 4.0,
+            sig_denom: 4.0,
+            ticks_per_beat: 1920.0,
+        })
+        .unwrap();
+
+    // Wait for user input to quit
+    println!("Press enter/return to quit...");
+    let mut user_input = String::new();
+    io::stdin().read_line(&mut user_input).ok();
+
+    active_client.release_timebase().ok();
+    if let Err(err) = active_client.deactivate() {
+        eprintln!("JACK exited with error: {err}");
+    };
+}
@@ -530,6 +530,25 @@ extern "C" {
     pub fn jack_free(ptr: *mut ::libc::c_void) -> ();
     pub fn jack_client_real_time_priority(arg1: *mut jack_client_t) -> ::libc::c_int;
     pub fn jack_client_max_real_time_priority(arg1: *mut jack_client_t) -> ::libc::c_int;
+    pub fn jack_acquire_real_time_scheduling(
+        thread: jack_native_thread_t,
+        priority: ::libc::c_int,
+    ) -> ::libc::c_int;
+    pub fn jack_drop_real_time_scheduling(thread: jack_native_thread_t) -> ::libc::c_int;
+    pub fn jack_client_create_thread(
+        client: *mut jack_client_t,
+        thread: *mut jack_native_thread_t,
+        priority: ::libc::c_int,
+        realtime: ::libc::c_int,
+        start_routine: ::std::option::Option<
+            unsafe extern "C" fn(arg1: *mut ::libc::c_void) -> *mut ::libc::c_void,
+        >,
+        arg: *mut ::libc::c_void,
+    ) -> ::libc::c_int;
+    pub fn jack_client_stop_thread(
+        client: *mut jack_client_t,
+        thread: jack_native_thread_t,
+    ) -> ::libc::c_int;
     pub fn jack_set_session_callback(
         client: *mut jack_client_t,
         session_callback: JackSessionCallback,
@@ -258,6 +258,20 @@ pub struct JackFunctions {
     jack_client_real_time_priority_impl: unsafe extern "C" fn(*mut jack_client_t) -> ::libc::c_int,
     jack_client_max_real_time_priority_impl:
         unsafe extern "C" fn(*mut jack_client_t) -> ::libc::c_int,
+    jack_acquire_real_time_scheduling_impl:
+        unsafe extern "C" fn(jack_native_thread_t, ::libc::c_int) -> ::libc::c_int,
+    jack_drop_real_time_scheduling_impl:
+        unsafe extern "C" fn(jack_native_thread_t) -> ::libc::c_int,
+    jack_client_create_thread_impl: unsafe extern "C" fn(
+        *mut jack_client_t,
+        *mut jack_native_thread_t,
+        ::libc::c_int,
+        ::libc::c_int,
+        ::std::option::Option<unsafe extern "C" fn(*mut ::libc::c_void) -> *mut ::libc::c_void>,
+        *mut ::libc::c_void,
+    ) -> ::libc::c_int,
+    jack_client_stop_thread_impl:
+        unsafe extern "C" fn(*mut jack_client_t, jack_native_thread_t) -> ::libc::c_int,
     jack_set_session_callback_impl: unsafe extern "C" fn(
         *mut jack_client_t,
         JackSessionCallback,
@@ -1586,6 +1600,63 @@ lazy_static! {
         let jack_client_max_real_time_priority_impl = *jack_client_max_real_time_priority_impl
             .deref()
             as unsafe extern "C" fn(arg1: *mut jack_client_t) -> ::libc::c_int;
+        let jack_acquire_real_time_scheduling_impl = library
+            .get::<unsafe extern "C" fn(
+                thread: jack_native_thread_t,
+                priority: ::libc::c_int,
+            ) -> ::libc::c_int>(b"jack_acquire_real_time_scheduling")
+            .unwrap();
+        let jack_acquire_real_time_scheduling_impl =
+            jack_acquire_real_time_scheduling_impl.into_raw();
+        let jack_acquire_real_time_scheduling_impl = *jack_acquire_real_time_scheduling_impl.deref()
+            as unsafe extern "C" fn(
+                thread: jack_native_thread_t,
+                priority: ::libc::c_int,
+            ) -> ::libc::c_int;
+        let jack_drop_real_time_scheduling_impl = library
+            .get::<unsafe extern "C" fn(thread: jack_native_thread_t) -> ::libc::c_int>(
+                b"jack_drop_real_time_scheduling",
+            )
+            .unwrap();
+        let jack_drop_real_time_scheduling_impl = jack_drop_real_time_scheduling_impl.into_raw();
+        let jack_drop_real_time_scheduling_impl = *jack_drop_real_time_scheduling_impl.deref()
+            as unsafe extern "C" fn(thread: jack_native_thread_t) -> ::libc::c_int;
+        let jack_client_create_thread_impl = library
+            .get::<unsafe extern "C" fn(
+                client: *mut jack_client_t,
+                thread: *mut jack_native_thread_t,
+                priority: ::libc::c_int,
+                realtime: ::libc::c_int,
+                start_routine: ::std::option::Option<
+                    unsafe extern "C" fn(arg1: *mut ::libc::c_void) -> *mut ::libc::c_void,
+                >,
+                arg: *mut ::libc::c_void,
+            ) -> ::libc::c_int>(b"jack_client_create_thread")
+            .unwrap();
+        let jack_client_create_thread_impl = jack_client_create_thread_impl.into_raw();
+        let jack_client_create_thread_impl = *jack_client_create_thread_impl.deref()
+            as unsafe extern "C" fn(
+                client: *mut jack_client_t,
+                thread: *mut jack_native_thread_t,
+                priority: ::libc::c_int,
+                realtime: ::libc::c_int,
+                start_routine: ::std::option::Option<
+                    unsafe extern "C" fn(arg1: *mut ::libc::c_void) -> *mut ::libc::c_void,
+                >,
+                arg: *mut ::libc::c_void,
+            ) -> ::libc::c_int;
+        let jack_client_stop_thread_impl = library
+            .get::<unsafe extern "C" fn(
+                client: *mut jack_client_t,
+                thread: jack_native_thread_t,
+            ) -> ::libc::c_int>(b"jack_client_stop_thread")
+            .unwrap();
+        let jack_client_stop_thread_impl = jack_client_stop_thread_impl.into_raw();
+        let jack_client_stop_thread_impl = *jack_client_stop_thread_impl.deref()
+            as unsafe extern "C" fn(
+                client: *mut jack_client_t,
+                thread: jack_native_thread_t,
+            ) -> ::libc::c_int;
         let jack_set_session_callback_impl = library
             .get::<unsafe extern "C" fn(
                 client: *mut jack_client_t,
@@ -2722,6 +2793,10 @@ lazy_static! {
             jack_free_impl,
             jack_client_real_time_priority_impl,
             jack_client_max_real_time_priority_impl,
+            jack_acquire_real_time_scheduling_impl,
+            jack_drop_real_time_scheduling_impl,
+            jack_client_create_thread_impl,
+            jack_client_stop_thread_impl,
             jack_set_session_callback_impl,
             jack_session_reply_impl,
             jack_session_event_free_impl,
@@ -3435,6 +3510,37 @@ pub unsafe fn jack_client_max_real_time_priority(arg1: *mut jack_client_t) -> ::
     let f = FUNCTIONS.jack_client_max_real_time_priority_impl;
     f(arg1)
 }
+pub unsafe fn jack_acquire_real_time_scheduling(
+    thread: jack_native_thread_t,
+    priority: ::libc::c_int,
+) -> ::libc::c_int {
+    let f = FUNCTIONS.jack_acquire_real_time_scheduling_impl;
+    f(thread, priority)
+}
+pub unsafe fn jack_drop_real_time_scheduling(thread: jack_native_thread_t) -> ::libc::c_int {
+    let f = FUNCTIONS.jack_drop_real_time_scheduling_impl;
+    f(thread)
+}
+pub unsafe fn jack_client_create_thread(
+    client: *mut jack_client_t,
+    thread: *mut jack_native_thread_t,
+    priority: ::libc::c_int,
+    realtime: ::libc::c_int,
+    start_routine: ::std::option::Option<
+        unsafe extern "C" fn(arg1: *mut ::libc::c_void) -> *mut ::libc::c_void,
+    >,
+    arg: *mut ::libc::c_void,
+) -> ::libc::c_int {
+    let f = FUNCTIONS.jack_client_create_thread_impl;
+    f(client, thread, priority, realtime, start_routine, arg)
+}
+pub unsafe fn jack_client_stop_thread(
+    client: *mut jack_client_t,
+    thread: jack_native_thread_t,
+) -> ::libc::c_int {
+    let f = FUNCTIONS.jack_client_stop_thread_impl;
+    f(client, thread)
+}
 pub unsafe fn jack_set_session_callback(
     client: *mut jack_client_t,
     session_callback: JackSessionCallback,
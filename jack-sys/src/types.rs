@@ -1,6 +1,8 @@
 #![allow(non_camel_case_types)]
 #[cfg(not(target_os = "windows"))]
 pub type jack_native_thread_t = ::libc::pthread_t;
+#[cfg(target_os = "windows")]
+pub type jack_native_thread_t = *mut ::libc::c_void;
 pub type jack_uuid_t = u64;
 pub type jack_shmsize_t = i32;
 pub type jack_nframes_t = u32;
@@ -63,8 +65,10 @@ pub type jack_latency_range_t = Struct__jack_latency_range;
 pub type JackProcessCallback = ::std::option::Option<
     unsafe extern "C" fn(nframes: jack_nframes_t, arg: *mut ::libc::c_void) -> ::libc::c_int,
 >;
-pub type JackThreadCallback =
-    ::std::option::Option<unsafe extern "C" fn(arg: *mut ::libc::c_void) -> *mut ::libc::c_void>;
+// Thread callbacks may be cancelled by JACK, which unwinds through them.
+pub type JackThreadCallback = ::std::option::Option<
+    unsafe extern "C-unwind" fn(arg: *mut ::libc::c_void) -> *mut ::libc::c_void,
+>;
 pub type JackThreadInitCallback =
     ::std::option::Option<unsafe extern "C" fn(arg: *mut ::libc::c_void) -> ()>;
 pub type JackGraphOrderCallback =
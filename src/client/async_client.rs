@@ -3,9 +3,13 @@ use std::fmt;
 use std::fmt::Debug;
 use std::mem;
 use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 use super::callbacks::clear_callbacks;
-use super::callbacks::{CallbackContext, NotificationHandler, ProcessHandler};
+use super::callbacks::{
+    timebase, CallbackContext, NotificationHandler, ProcessHandler, ProcessThread,
+    ProcessThreadHandler, ProcessThreadState, TimebaseContext, TimebaseHandler,
+};
 use crate::client::client_impl::Client;
 use crate::client::common::CREATE_OR_DESTROY_CLIENT_MUTEX;
 use crate::Error;
@@ -35,6 +39,11 @@ use crate::Error;
 #[must_use = "The jack client is shut down when the AsyncClient is dropped. You most likely want to keep this alive and manually tear down with `AsyncClient::deactivate`."]
 pub struct AsyncClient<N, P> {
     callback: Option<Box<CallbackContext<N, P>>>,
+    // Allocated with the first timebase handler and kept until deactivation, along with every
+    // handler that was set.
+    timebase: Option<Box<TimebaseContext>>,
+    is_timebase_master: bool,
+    process_thread: Option<Arc<ProcessThreadState>>,
 }
 
 unsafe impl<N, P> Send for AsyncClient<N, P> {}
@@ -68,6 +77,57 @@ where
             match res {
                 0 => Ok(AsyncClient {
                     callback: Some(callback_context),
+                    timebase: None,
+                    is_timebase_master: false,
+                    process_thread: None,
+                }),
+                _ => {
+                    mem::forget(callback_context);
+                    Err(Error::ClientActivationError)
+                }
+            }
+        }
+    }
+}
+
+impl<N, T> AsyncClient<N, ProcessThread<T>>
+where
+    N: 'static + Send + Sync + NotificationHandler,
+    T: 'static + Send + ProcessThreadHandler,
+{
+    /// Tell the JACK server that the program is ready to start processing audio. JACK will call
+    /// the methods specified by the `NotificationHandler` and run the `ProcessThreadHandler` on
+    /// its process thread.
+    ///
+    /// On failure, either `Err(Error::CallbackRegistrationError)` or
+    /// `Err(Error::ClientActivationError)` is returned.
+    ///
+    /// `notification_handler` and `process_thread_handler` are consumed, but they are returned
+    /// when `Client::deactivate` is called.
+    pub fn with_process_thread(
+        client: Client,
+        notification_handler: N,
+        process_thread_handler: T,
+    ) -> Result<Self, Error> {
+        let _m = CREATE_OR_DESTROY_CLIENT_MUTEX.lock().ok();
+        unsafe {
+            let process = ProcessThread::new(process_thread_handler);
+            let process_thread = process.state().clone();
+            let mut callback_context = Box::new(CallbackContext {
+                client,
+                notification: notification_handler,
+                process,
+                is_valid_for_callback: AtomicBool::new(true),
+                has_panic: AtomicBool::new(false),
+            });
+            CallbackContext::register_process_thread_callbacks(&mut callback_context)?;
+            let res = j::jack_activate(callback_context.client.raw());
+            match res {
+                0 => Ok(AsyncClient {
+                    callback: Some(callback_context),
+                    timebase: None,
+                    is_timebase_master: false,
+                    process_thread: Some(process_thread),
                 }),
                 _ => {
                     mem::forget(callback_context);
@@ -86,6 +146,59 @@ impl<N, P> AsyncClient<N, P> {
         &callback.client
     }
 
+    /// Register `handler` to become the timebase master. See [`TimebaseHandler`] for details.
+    ///
+    /// If `T::CONDITIONAL` is `true` and there is already a timebase master,
+    /// `Err(Error::TimebaseMasterExists)` is returned. Otherwise, any existing timebase master,
+    /// including a handler previously registered by this client, is replaced.
+    pub fn set_timebase_handler<T>(&mut self, handler: T) -> Result<(), Error>
+    where
+        N: 'static + Send + Sync + NotificationHandler,
+        P: 'static + Send + ProcessHandler,
+        T: 'static + TimebaseHandler,
+    {
+        let callback = self.callback.as_ref().unwrap();
+        let context = self
+            .timebase
+            .get_or_insert_with(|| Box::new(TimebaseContext::new(callback)));
+        let data = context.as_mut() as *mut TimebaseContext as *mut libc::c_void;
+        let res = unsafe {
+            j::jack_set_timebase_callback(
+                callback.client.raw(),
+                libc::c_int::from(T::CONDITIONAL),
+                Some(timebase),
+                data,
+            )
+        };
+        match res {
+            0 => {
+                context.set_handler(Box::new(handler));
+                self.is_timebase_master = true;
+                Ok(())
+            }
+            libc::EBUSY => Err(Error::TimebaseMasterExists),
+            error_code => Err(Error::UnknownError { error_code }),
+        }
+    }
+
+    /// Stop being the timebase master. The registered [`TimebaseHandler`] will no longer be
+    /// called.
+    ///
+    /// `Err(Error::NotTimebaseMaster)` is returned if this client is not the timebase master, for
+    /// example, if another client has taken over.
+    pub fn release_timebase(&mut self) -> Result<(), Error> {
+        let res = unsafe { j::jack_release_timebase(self.as_client().raw()) };
+        // On other errors, this client may still be the master and is released on deactivation.
+        if res == 0 || res == libc::ESRCH {
+            self.is_timebase_master = false;
+        }
+        match res {
+            0 => Ok(()),
+            libc::ESRCH => Err(Error::NotTimebaseMaster),
+            error_code => Err(Error::UnknownError { error_code }),
+        }
+    }
+
     /// Tell the JACK server to remove this client from the process graph.  Also, disconnect all
     /// ports belonging to it since inactive clients have no port connections.
     ///
@@ -108,12 +221,27 @@ impl<N, P> AsyncClient<N, P> {
             drop(m);
             return Err(Error::ClientIsNoLongerAlive);
         }
+        if self.is_timebase_master {
+            // Best effort, another client may have taken over.
+            unsafe { j::jack_release_timebase(self.as_client().raw()) };
+            self.is_timebase_master = false;
+        }
         let cb = self.callback.take().ok_or(Error::ClientIsNoLongerAlive)?;
+        // Let a process thread handler return before JACK cancels its thread.
+        if let Some(process_thread) = &self.process_thread {
+            process_thread.stop();
+        }
         // deactivate
         if unsafe { j::jack_deactivate(cb.client.raw()) } != 0 {
             drop(m);
             return Err(Error::ClientDeactivationError);
         }
+        self.timebase = None;
+
+        // JACK refuses to clear the process callback while a process thread is set.
+        if self.process_thread.is_some() {
+            unsafe { j::jack_set_process_thread(cb.client.raw(), None, std::ptr::null_mut()) };
+        }
 
         // clear the callbacks
         unsafe { clear_callbacks(cb.client.raw()) }?;
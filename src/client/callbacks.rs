@@ -2,13 +2,21 @@ use jack_sys as j;
 use std::{
     ffi,
     panic::catch_unwind,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use crate::{Client, ClientStatus, Control, Error, Frames, PortId, ProcessScope};
+use crate::{
+    Client, ClientStatus, Control, Error, Frames, LatencyType, PortId, ProcessCycles, ProcessScope,
+};
 
 /// Specifies callbacks for JACK.
 pub trait NotificationHandler: Send {
+    /// Indicates whether or not this handler computes the latencies of the client's ports in
+    /// [`NotificationHandler::latency`].
+    const LATENCY: bool = false;
+
     /// Called just once after the creation of the thread in which all other
     /// callbacks will be
     /// handled.
@@ -73,6 +81,25 @@ pub trait NotificationHandler: Send {
     fn xrun(&mut self, _: &Client) -> Control {
         Control::Continue
     }
+
+    /// Called whenever the latency of the graph needs to be recalculated. It is called once with
+    /// `LatencyType::Capture` and once with `LatencyType::Playback`.
+    ///
+    /// Ignored unless Self::LATENCY == true. Registering a latency callback turns off JACK's own
+    /// latency propagation for the client, so the handler is then responsible for the latency of
+    /// all its ports.
+    ///
+    /// Clients that add latency between their inputs and outputs should use
+    /// [`Port::get_latency_range`](crate::Port::get_latency_range) and
+    /// [`Port::set_latency_range`](crate::Port::set_latency_range) to propagate it. For `Capture`,
+    /// the latency of the input ports plus the internal delay is set on the output ports. For
+    /// `Playback`, the latency of the output ports plus the internal delay is set on the input
+    /// ports. Clients that do not set latencies get the maximum of their connected ports
+    /// propagated by JACK, as long as they do not set `LATENCY`.
+    ///
+    /// See [Managing and determining latency](https://jackaudio.org/api/group__LatencyFunctions.html)
+    /// for more details.
+    fn latency(&mut self, _: &Client, _mode: LatencyType) {}
 }
 
 /// Specifies real-time processing.
@@ -122,6 +149,177 @@ pub trait ProcessHandler: Send {
     }
 }
 
+/// Specifies real-time processing where the handler owns the process loop.
+///
+/// This is an alternative to [`ProcessHandler`]. Instead of JACK calling `process` once per cycle,
+/// [`ProcessThreadHandler::run`] is called once on JACK's process thread and loops over the
+/// cycles itself. This allows doing work between cycles without a second thread.
+///
+/// Activate with [`Client::activate_process_thread`](crate::Client::activate_process_thread).
+pub trait ProcessThreadHandler: Send {
+    /// Indicates whether or not this process handler represents a
+    /// slow-sync client
+    const SLOW_SYNC: bool = false;
+
+    /// Called once on the process thread after activation.
+    ///
+    /// Each cycle starts with [`ProcessCycles::wait`], which blocks until JACK starts the next
+    /// cycle, and ends with [`ProcessCycle::signal`](crate::ProcessCycle::signal). The handler
+    /// should loop until `wait` returns `None`.
+    ///
+    /// The code between `wait` and `signal` needs to be suitable for real-time execution, see
+    /// [`ProcessHandler::process`]. The rest of the time is taken away from the other clients in
+    /// the graph, so it should stay short as well.
+    ///
+    /// On deactivation, `wait` returns `None` and `run` should return promptly. If it has not
+    /// returned after one second, JACK cancels the thread. Cancellation unwinds the stack, which
+    /// is undefined behavior if it passes through values that need to be dropped, so the handler
+    /// should keep its state in `self` rather than in local variables of `run`.
+    fn run(&mut self, _: &Client, cycles: &mut ProcessCycles);
+
+    /// Called whenever the size of the buffer that will be passed to `process`
+    /// is about to change, and once before the first cycle.
+    ///
+    /// See [`ProcessHandler::buffer_size`].
+    fn buffer_size(&mut self, _: &Client, _size: Frames) -> Control {
+        Control::Continue
+    }
+
+    /// For slow-sync clients, called periodically when the transport position
+    /// is changed. See [`ProcessHandler::sync`].
+    ///
+    /// Ignored unless Self::SLOW_SYNC == true.
+    fn sync(
+        &mut self,
+        _: &Client,
+        _state: crate::TransportState,
+        _pos: &crate::TransportPosition,
+    ) -> bool {
+        true
+    }
+}
+
+/// Adapts a [`ProcessThreadHandler`] to be used as the process handler of an [`AsyncClient`].
+///
+/// Created by [`Client::activate_process_thread`](crate::Client::activate_process_thread). The
+/// handler is returned within this wrapper by
+/// [`AsyncClient::deactivate`](crate::AsyncClient::deactivate).
+///
+/// [`AsyncClient`]: crate::AsyncClient
+pub struct ProcessThread<T> {
+    handler: T,
+    state: Arc<ProcessThreadState>,
+}
+
+impl<T> ProcessThread<T> {
+    pub(crate) fn new(handler: T) -> Self {
+        ProcessThread {
+            handler,
+            state: Arc::default(),
+        }
+    }
+
+    pub(crate) fn state(&self) -> &Arc<ProcessThreadState> {
+        &self.state
+    }
+
+    /// The wrapped process thread handler.
+    pub fn handler(&self) -> &T {
+        &self.handler
+    }
+
+    /// Consume the wrapper and return the wrapped process thread handler.
+    pub fn into_inner(self) -> T {
+        self.handler
+    }
+}
+
+/// Lets deactivation end a [`ProcessThreadHandler`] before JACK cancels its thread.
+#[derive(Debug, Default)]
+pub(crate) struct ProcessThreadState {
+    stop_requested: AtomicBool,
+    /// Set when the handler signals `Control::Quit`.
+    quit_requested: AtomicBool,
+    is_finished: AtomicBool,
+}
+
+impl ProcessThreadState {
+    /// The time deactivation waits for the handler to return from `run`.
+    const STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub(crate) fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn request_quit(&self) {
+        self.quit_requested.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_quit_requested(&self) -> bool {
+        self.quit_requested.load(Ordering::Acquire)
+    }
+
+    /// Ask the handler to return from `run` and wait until it has, or until the timeout.
+    pub(crate) fn stop(&self) {
+        self.stop_requested.store(true, Ordering::Release);
+        let start = Instant::now();
+        while !self.is_finished.load(Ordering::Acquire) && start.elapsed() < Self::STOP_TIMEOUT {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl<T: ProcessThreadHandler> ProcessHandler for ProcessThread<T> {
+    const SLOW_SYNC: bool = T::SLOW_SYNC;
+
+    /// Never called, JACK does not use the process callback for clients with a process thread.
+    fn process(&mut self, _: &Client, _: &ProcessScope) -> Control {
+        Control::Quit
+    }
+
+    fn buffer_size(&mut self, client: &Client, size: Frames) -> Control {
+        self.handler.buffer_size(client, size)
+    }
+
+    fn sync(
+        &mut self,
+        client: &Client,
+        state: crate::TransportState,
+        pos: &crate::TransportPosition,
+    ) -> bool {
+        self.handler.sync(client, state, pos)
+    }
+}
+
+/// Specifies the callback of a timebase master, the client that is responsible for extended
+/// position information such as bars, beats, ticks, tempo and time signature.
+///
+/// Register the handler with [`AsyncClient::set_timebase_handler`](crate::AsyncClient::set_timebase_handler).
+pub trait TimebaseHandler: Send {
+    /// Indicates whether or not this handler should only become the timebase master if there is
+    /// no timebase master already. If `true` and there is already a master,
+    /// `Err(Error::TimebaseMasterExists)` is returned on registration.
+    const CONDITIONAL: bool = false;
+
+    /// Called after the process callbacks of the current cycle to update the extended position
+    /// information in `pos`.
+    ///
+    /// `is_new_position` is `true` on the first cycle after becoming the timebase master and after
+    /// the transport has been repositioned, in which case the handler should compute the BBT
+    /// information from `pos.frame()`. Otherwise, the handler may extrapolate the BBT information
+    /// from the previous cycle, which is still present in `pos`.
+    ///
+    /// It needs to be suitable for real-time execution, see [`ProcessHandler::process`].
+    fn timebase(
+        &mut self,
+        _: &Client,
+        state: crate::TransportState,
+        n_frames: Frames,
+        pos: &mut crate::TransportPosition,
+        is_new_position: bool,
+    );
+}
+
 unsafe extern "C" fn thread_init_callback<N, P>(data: *mut libc::c_void)
 where
     N: 'static + Send + Sync + NotificationHandler,
@@ -199,6 +397,44 @@ where
     }
 }
 
+// JACK ends the process thread with `pthread_cancel` if it is still running after deactivation,
+// and with `pthread_exit` when the client quits, both of which unwind through this function.
+// Deactivation asks the handler to return first, see `ProcessThreadState::stop`, and quitting
+// waits for it to return, so nothing here needs dropping while JACK may end the thread.
+unsafe extern "C-unwind" fn process_thread<N, T>(data: *mut libc::c_void) -> *mut libc::c_void
+where
+    N: 'static + Send + Sync + NotificationHandler,
+    T: 'static + Send + ProcessThreadHandler,
+{
+    let (state, client_ptr): (*const ProcessThreadState, _) = {
+        let ctx = &*(data as *const CallbackContext<N, ProcessThread<T>>);
+        (ctx.process.state.as_ref(), ctx.client.raw())
+    };
+    let res = catch_unwind(|| {
+        let Some(ctx) = CallbackContext::<N, ProcessThread<T>>::from_raw(data) else {
+            return;
+        };
+        let mut cycles = ProcessCycles::from_raw(ctx.client.raw(), state);
+        ctx.process.handler.run(&ctx.client, &mut cycles);
+    });
+    if let Err(err) = res {
+        if let Some(ctx) = CallbackContext::<N, ProcessThread<T>>::from_raw(data) {
+            ctx.mark_invalid(true)
+        }
+        eprintln!("JACK process thread panicked.\n{err:?}");
+        std::mem::forget(err);
+    }
+    (*state).is_finished.store(true, Ordering::Release);
+    if (*state).is_quit_requested() && !(*state).is_stop_requested() {
+        // Only now that `run` has returned, let JACK stop processing for this client. JACK ends
+        // the thread within `jack_cycle_signal`.
+        if j::jack_cycle_wait(client_ptr) != 0 {
+            j::jack_cycle_signal(client_ptr, Control::Quit.to_ffi());
+        }
+    }
+    std::ptr::null_mut()
+}
+
 unsafe extern "C" fn sync<N, P>(
     state: jack_sys::jack_transport_state_t,
     pos: *mut jack_sys::jack_position_t,
@@ -364,7 +600,6 @@ unsafe extern "C" fn port_registration<N, P>(
     }
 }
 
-#[allow(dead_code)] // TODO: remove once it can be registered
 unsafe extern "C" fn port_rename<N, P>(
     port_id: PortId,
     old_name: *const libc::c_char,
@@ -484,6 +719,138 @@ where
     }
 }
 
+unsafe extern "C" fn latency<N, P>(mode: j::jack_latency_callback_mode_t, data: *mut libc::c_void)
+where
+    N: 'static + Send + Sync + NotificationHandler,
+    P: 'static + Send + ProcessHandler,
+{
+    let res = catch_unwind(|| {
+        let Some(ctx) = CallbackContext::<N, P>::from_raw(data) else {
+            return;
+        };
+        ctx.notification
+            .latency(&ctx.client, LatencyType::from_ffi(mode));
+    });
+    if let Err(err) = res {
+        if let Some(ctx) = CallbackContext::<N, P>::from_raw(data) {
+            ctx.mark_invalid(true)
+        }
+        eprintln!("{err:?}");
+        std::mem::forget(err);
+    }
+}
+
+/// The object safe part of [`TimebaseHandler`], so that handlers of any type are called by the
+/// same callback.
+pub(crate) trait DynTimebaseHandler: Send {
+    fn timebase(
+        &mut self,
+        client: &Client,
+        state: crate::TransportState,
+        n_frames: Frames,
+        pos: &mut crate::TransportPosition,
+        is_new_position: bool,
+    );
+}
+
+impl<T: TimebaseHandler> DynTimebaseHandler for T {
+    fn timebase(
+        &mut self,
+        client: &Client,
+        state: crate::TransportState,
+        n_frames: Frames,
+        pos: &mut crate::TransportPosition,
+        is_new_position: bool,
+    ) {
+        TimebaseHandler::timebase(self, client, state, n_frames, pos, is_new_position)
+    }
+}
+
+/// The information used by JACK to call the [`TimebaseHandler`] of an `AsyncClient`.
+///
+/// JACK does not update the callback and its argument atomically, so a client registers the same
+/// `timebase` callback with the same context every time and only the handler within the context
+/// is replaced.
+pub(crate) struct TimebaseContext {
+    client: *const Client,
+    is_valid_for_callback: *const AtomicBool,
+    has_panic: *const AtomicBool,
+    /// The current handler, null before the first one is set.
+    handler: AtomicPtr<Box<dyn DynTimebaseHandler>>,
+    /// Handlers that were replaced. The process thread may still be running one right after it
+    /// was replaced, so they are only deallocated along with the context.
+    replaced: Vec<*mut Box<dyn DynTimebaseHandler>>,
+}
+
+unsafe impl Send for TimebaseContext {}
+
+impl TimebaseContext {
+    /// Create a context without a handler for the client of `callback`. The context must not
+    /// outlive `callback`.
+    pub(crate) fn new<N, P>(callback: &CallbackContext<N, P>) -> Self {
+        TimebaseContext {
+            client: &callback.client,
+            is_valid_for_callback: &callback.is_valid_for_callback,
+            has_panic: &callback.has_panic,
+            handler: AtomicPtr::new(std::ptr::null_mut()),
+            replaced: Vec::new(),
+        }
+    }
+
+    /// Make `handler` the handler called by `timebase`, keeping the previous one alive.
+    pub(crate) fn set_handler(&mut self, handler: Box<dyn DynTimebaseHandler>) {
+        let handler = Box::into_raw(Box::new(handler));
+        let previous = self.handler.swap(handler, Ordering::AcqRel);
+        if !previous.is_null() {
+            self.replaced.push(previous);
+        }
+    }
+}
+
+impl Drop for TimebaseContext {
+    fn drop(&mut self) {
+        let handler = *self.handler.get_mut();
+        for handler in self.replaced.drain(..).chain(Some(handler)) {
+            if !handler.is_null() {
+                drop(unsafe { Box::from_raw(handler) });
+            }
+        }
+    }
+}
+
+pub(crate) unsafe extern "C" fn timebase(
+    state: j::jack_transport_state_t,
+    n_frames: Frames,
+    pos: *mut j::jack_position_t,
+    new_pos: libc::c_int,
+    data: *mut libc::c_void,
+) {
+    let res = catch_unwind(|| {
+        let ctx = &*(data as *const TimebaseContext);
+        if !(*ctx.is_valid_for_callback).load(Ordering::Relaxed) {
+            return;
+        }
+        let handler = ctx.handler.load(Ordering::Acquire);
+        if handler.is_null() {
+            return;
+        }
+        (*handler).timebase(
+            &*ctx.client,
+            crate::Transport::state_from_ffi(state),
+            n_frames,
+            &mut *(pos as *mut crate::TransportPosition),
+            !matches!(new_pos, 0),
+        );
+    });
+    if let Err(err) = res {
+        let ctx = &*(data as *const TimebaseContext);
+        (*ctx.is_valid_for_callback).store(false, Ordering::Relaxed);
+        (*ctx.has_panic).store(true, Ordering::Relaxed);
+        eprintln!("{err:?}");
+        std::mem::forget(err);
+    }
+}
+
 /// Unsafe ffi wrapper that clears the callbacks registered to `client`.
 ///
 /// This is mostly for use within the jack crate itself.
@@ -569,18 +936,24 @@ where
     /// # TODO
     ///
     /// * Handled failed registrations
-    /// * Fix `jack_set_port_rename_callback`
     ///
     /// # Unsafe
     ///
     /// * makes ffi calls
     /// * `handler` will not be automatically deallocated.
     pub unsafe fn register_callbacks(b: &mut Box<Self>) -> Result<(), Error> {
+        let data_ptr = CallbackContext::raw(b);
+        let client = b.client.raw();
+        j::jack_set_process_callback(client, Some(process::<N, P>), data_ptr);
+        Self::register_common_callbacks(b)
+    }
+
+    /// Registers all callbacks except for the process callback.
+    unsafe fn register_common_callbacks(b: &mut Box<Self>) -> Result<(), Error> {
         let data_ptr = CallbackContext::raw(b);
         let client = b.client.raw();
         j::jack_set_thread_init_callback(client, Some(thread_init_callback::<N, P>), data_ptr);
         j::jack_on_info_shutdown(client, Some(shutdown::<N, P>), data_ptr);
-        j::jack_set_process_callback(client, Some(process::<N, P>), data_ptr);
         if P::SLOW_SYNC {
             j::jack_set_sync_callback(client, Some(sync::<N, P>), data_ptr);
         }
@@ -593,11 +966,37 @@ where
             data_ptr,
         );
         j::jack_set_port_registration_callback(client, Some(port_registration::<N, P>), data_ptr);
-        // doesn't compile for testing since it is a weak export
-        // j::jack_set_port_rename_callback(client, Some(port_rename::<N, P), data_ptr);
+        j::jack_set_port_rename_callback(client, Some(port_rename::<N, P>), data_ptr);
         j::jack_set_port_connect_callback(client, Some(port_connect::<N, P>), data_ptr);
         j::jack_set_graph_order_callback(client, Some(graph_order::<N, P>), data_ptr);
         j::jack_set_xrun_callback(client, Some(xrun::<N, P>), data_ptr);
+        if N::LATENCY {
+            j::jack_set_latency_callback(client, Some(latency::<N, P>), data_ptr);
+        }
         Ok(())
     }
 }
+
+impl<N, T> CallbackContext<N, ProcessThread<T>>
+where
+    N: 'static + Send + Sync + NotificationHandler,
+    T: 'static + Send + ProcessThreadHandler,
+{
+    /// Registers methods from `handler` to be used by JACK with `client`, with
+    /// [`ProcessThreadHandler::run`] as the process thread instead of a process callback.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    ///
+    /// # Unsafe
+    ///
+    /// * makes ffi calls
+    /// * `handler` will not be automatically deallocated.
+    pub unsafe fn register_process_thread_callbacks(b: &mut Box<Self>) -> Result<(), Error> {
+        let data_ptr = CallbackContext::raw(b);
+        let client = b.client.raw();
+        if j::jack_set_process_thread(client, Some(process_thread::<N, T>), data_ptr) != 0 {
+            return Err(Error::CallbackRegistrationError);
+        }
+        Self::register_common_callbacks(b)
+    }
+}
@@ -3,14 +3,17 @@ use std::fmt::Debug;
 use std::sync::Arc;
 use std::{ffi, fmt, ptr};
 
+use crate::client::callbacks::ProcessThreadState;
 use crate::client::common::CREATE_OR_DESTROY_CLIENT_MUTEX;
+use crate::client::thread::{self, RealtimeJoinHandle};
 use crate::jack_enums::CodeOrMessage;
 use crate::jack_utils::collect_strs;
 use crate::properties::PropertyChangeHandler;
 use crate::transport::Transport;
 use crate::{
-    AsyncClient, ClientOptions, ClientStatus, Error, Frames, NotificationHandler, Port, PortFlags,
-    PortId, PortSpec, ProcessHandler, Time, Unowned,
+    AsyncClient, AudioIn, AudioOut, ClientOptions, ClientStatus, Control, Error, Frames,
+    NotificationHandler, Port, PortFlags, PortId, PortSpec, PortTie, ProcessHandler, ProcessThread,
+    ProcessThreadHandler, SignalType, Time, Unowned, Uuid,
 };
 
 /// A client to interact with a JACK server.
@@ -87,6 +90,21 @@ impl Client {
         AsyncClient::new(self, notification_handler, process_handler)
     }
 
+    /// Begin processing in real-time using the specified `NotificationHandler` and
+    /// `ProcessThreadHandler`. Unlike [`Client::activate_async`], the handler owns the process
+    /// loop, see [`ProcessThreadHandler`] for details.
+    pub fn activate_process_thread<N, T>(
+        self,
+        notification_handler: N,
+        process_thread_handler: T,
+    ) -> Result<AsyncClient<N, ProcessThread<T>>, Error>
+    where
+        N: 'static + Send + Sync + NotificationHandler,
+        T: 'static + Send + ProcessThreadHandler,
+    {
+        AsyncClient::with_process_thread(self, notification_handler, process_thread_handler)
+    }
+
     /// Return JACK's current system time in microseconds, using the JACK clock
     /// source.
     ///
@@ -114,6 +132,24 @@ impl Client {
         unsafe { j::jack_cpu_load(self.raw()) }
     }
 
+    /// The delay in microseconds of the most recent xrun, as measured by JACK when it happened.
+    ///
+    /// Usually queried from [`NotificationHandler::xrun`].
+    pub fn xrun_delayed_usecs(&self) -> f32 {
+        unsafe { j::jack_get_xrun_delayed_usecs(self.raw()) }
+    }
+
+    /// The maximum delay in microseconds of the process cycle wakeup since the JACK server
+    /// started, or since the last call to [`Client::reset_max_delayed_usecs`].
+    pub fn max_delayed_usecs(&self) -> f32 {
+        unsafe { j::jack_get_max_delayed_usecs(self.raw()) }
+    }
+
+    /// Reset the value returned by [`Client::max_delayed_usecs`].
+    pub fn reset_max_delayed_usecs(&self) {
+        unsafe { j::jack_reset_max_delayed_usecs(self.raw()) }
+    }
+
     /// Get the name of the current client. This may differ from the name requested by `Client::new`
     /// as JACK will may rename a client if necessary (ie: name collision, name too long). The name
     /// will only the be different than the one passed to `Client::new` if the `ClientStatus` was
@@ -145,33 +181,33 @@ impl Client {
         }
     }
 
-    /// Get the numeric `uuid` of this client.
+    /// Get the `Uuid` of this client.
     ///
     /// # Remarks
     ///
     /// * Deallocates, not realtime safe.
-    pub fn uuid(&self) -> j::jack_uuid_t {
+    pub fn uuid(&self) -> Uuid {
         unsafe {
             let mut uuid: j::jack_uuid_t = Default::default();
             let uuid_s = j::jack_client_get_uuid(self.raw());
             assert!(!uuid_s.is_null());
             assert_eq!(0, j::jack_uuid_parse(uuid_s, &mut uuid));
             j::jack_free(uuid_s as _);
-            uuid
+            Uuid::from_raw(uuid)
         }
     }
 
-    /// Get the numeric `uuid` of a client by name; returns None if client does not exist
+    /// Get the `Uuid` of a client by name; returns None if client does not exist
     /// # Remarks
     /// * Not realtime safe
-    pub fn uuid_of_client_by_name(&self, name: &str) -> Option<jack_sys::jack_uuid_t> {
+    pub fn uuid_of_client_by_name(&self, name: &str) -> Option<Uuid> {
         Self::uuid_of_client_by_name_raw(self.raw(), name)
     }
 
     pub(crate) fn uuid_of_client_by_name_raw(
         raw: *mut jack_sys::jack_client_t,
         name: &str,
-    ) -> Option<jack_sys::jack_uuid_t> {
+    ) -> Option<Uuid> {
         let name = ffi::CString::new(name).unwrap();
         let mut uuid: jack_sys::jack_uuid_t = Default::default();
         unsafe {
@@ -182,7 +218,7 @@ impl Client {
             assert_eq!(0, jack_sys::jack_uuid_parse(uuid_s, &mut uuid));
             jack_sys::jack_free(uuid_s as _);
         }
-        Some(uuid)
+        Some(Uuid::from_raw(uuid))
     }
     /// Get a String representation of the `uuid` of this client.
     ///
@@ -217,11 +253,11 @@ impl Client {
         }
     }
 
-    /// Get the name of a client by its numeric uuid.
-    pub fn name_by_uuid(&self, uuid: j::jack_uuid_t) -> Option<String> {
+    /// Get the name of a client by its `Uuid`.
+    pub fn name_by_uuid(&self, uuid: Uuid) -> Option<String> {
         let mut uuid_s = ['\0' as _; 37]; //jack_uuid_unparse expects an array of length 37
         unsafe {
-            j::jack_uuid_unparse(uuid, uuid_s.as_mut_ptr());
+            j::jack_uuid_unparse(uuid.raw(), uuid_s.as_mut_ptr());
             self.name_by_uuid_raw(uuid_s.as_ptr())
         }
     }
@@ -264,11 +300,33 @@ impl Client {
         }
     }
 
+    /// Returns the names of the audio ports that carry `signal_type`. Audio ports without a signal
+    /// type are considered to carry audio.
+    ///
+    /// `port_name_pattern` and `flags` select ports like in [`Client::ports`].
+    pub fn ports_with_signal_type(
+        &self,
+        port_name_pattern: Option<&str>,
+        signal_type: SignalType,
+        flags: PortFlags,
+    ) -> Vec<String> {
+        self.ports(port_name_pattern, Some(j::FLOAT_MONO_AUDIO), flags)
+            .into_iter()
+            .filter(|name| {
+                self.port_by_name(name)
+                    .and_then(|p| p.effective_signal_type().ok().flatten())
+                    == Some(signal_type)
+            })
+            .collect()
+    }
+
     /// Create a new port for the client. This is an object used for moving data of any type in or
     /// out of the client. Ports may be connected in various ways.
     ///
     /// The `port_spec` specifies the IO direction and data type. Oftentimes, the built-in types
-    /// (`AudioIn`, `AudioOut`, `MidiIn`, `MidiOut`) can be used.
+    /// (`AudioIn`, `AudioOut`, `CvIn`, `CvOut`, `MidiIn`, `MidiOut`) can be used. If the spec has a
+    /// signal type, it is set as metadata right after registration and the port is unregistered
+    /// again if that fails.
     ///
     /// Each port has a short name. The port's full name contains the name of the client
     /// concatenated with a colon (:) followed by its short name. `Port::name_size()` is the maximum
@@ -296,10 +354,18 @@ impl Client {
             )
         };
         if pp.is_null() {
-            Err(Error::PortRegistrationError(port_name.to_string()))
-        } else {
-            Ok(unsafe { Port::from_raw(port_spec, self.raw(), pp, Arc::downgrade(&self.1)) })
+            return Err(Error::PortRegistrationError(port_name.to_string()));
+        }
+        let signal_type = port_spec.jack_signal_type();
+        let port = unsafe { Port::from_raw(port_spec, self.raw(), pp, Arc::downgrade(&self.1)) };
+        if let Some(signal_type) = signal_type {
+            // Do not leave behind a port that is missing its signal type.
+            if let Err(err) = port.set_signal_type(signal_type) {
+                unsafe { j::jack_port_unregister(self.raw(), pp) };
+                return Err(err);
+            }
         }
+        Ok(port)
     }
 
     /// Get a `Port` by its port id.
@@ -421,6 +487,20 @@ impl Client {
         unsafe { j::jack_time_to_frames(self.raw(), t) }
     }
 
+    /// Request a complete recomputation of all port latencies. This can be called by a client that
+    /// has just changed the internal latency of its port using `Port::set_latency_range` and wants
+    /// to ensure that all signal pathways in the graph are updated with respect to the values that
+    /// will be returned by `Port::get_latency_range`. It allows a client to change multiple port
+    /// latencies without triggering a recompute for each change.
+    ///
+    /// This calls `NotificationHandler::latency` for all active clients.
+    pub fn recompute_total_latencies(&self) -> Result<(), Error> {
+        match unsafe { j::jack_recompute_total_latencies(self.raw()) } {
+            0 => Ok(()),
+            error_code => Err(Error::UnknownError { error_code }),
+        }
+    }
+
     /// Returns `true` if the port `port` belongs to this client.
     pub fn is_mine<PS: PortSpec>(&self, port: &Port<PS>) -> bool {
         matches!(unsafe { j::jack_port_is_mine(self.raw(), port.raw()) }, 1)
@@ -450,30 +530,84 @@ impl Client {
         }
     }
 
-    // TODO implement
-    // /// Start/Stop JACK's "freewheel" mode.
-    // ///
-    // /// When in "freewheel" mode, JACK no longer waits for any external event to
-    // /// begin the start of the next process cycle. As a result, freewheel mode
-    // /// causes "faster than real-time" execution of a JACK graph. If possessed,
-    // /// real-time scheduling is dropped when entering freewheel mode, and if
-    // /// appropriate it is reacquired when stopping.
-    // ///
-    // /// IMPORTANT: on systems using capabilities to provide real-time scheduling
-    // /// (i.e. Linux Kernel 2.4), if enabling freewheel, this function must be
-    // /// called from the thread that originally called `self.activate()`. This
-    // /// restriction does not apply to other systems (e.g. Linux Kernel 2.6 or OS
-    // /// X).
-    // pub pub fn set_freewheel(&self, enable: bool) -> Result<(), Error> {
-    //     let onoff = match enable {
-    //         true => 0,
-    //         false => 1,
-    //     };
-    //     match unsafe { j::jack_set_freewheel(self.raw(), onoff) } {
-    //         0 => Ok(()),
-    //         _ => Err(Error::FreewheelError),
-    //     }
-    // }
+    /// Start/Stop JACK's "freewheel" mode.
+    ///
+    /// When in "freewheel" mode, JACK no longer waits for any external event to
+    /// begin the start of the next process cycle. As a result, freewheel mode
+    /// causes "faster than real-time" execution of a JACK graph. If possessed,
+    /// real-time scheduling is dropped when entering freewheel mode, and if
+    /// appropriate it is reacquired when stopping.
+    ///
+    /// This must not be called from within the process callback. Clients are notified of the
+    /// change through [`NotificationHandler::freewheel`](crate::NotificationHandler::freewheel).
+    ///
+    /// IMPORTANT: on systems using capabilities to provide real-time scheduling
+    /// (i.e. Linux Kernel 2.4), if enabling freewheel, this function must be
+    /// called from the thread that originally called `self.activate()`. This
+    /// restriction does not apply to other systems (e.g. Linux Kernel 2.6 or OS
+    /// X).
+    pub fn set_freewheel(&self, enable: bool) -> Result<(), Error> {
+        match unsafe { j::jack_set_freewheel(self.raw(), i32::from(enable)) } {
+            0 => Ok(()),
+            _ => Err(Error::FreewheelError),
+        }
+    }
+
+    /// Returns `true` if JACK is running with real-time scheduling.
+    pub fn is_realtime(&self) -> bool {
+        unsafe { j::jack_is_realtime(self.raw()) != 0 }
+    }
+
+    /// The priority of the process thread, or `None` if JACK is not running with real-time
+    /// scheduling.
+    pub fn real_time_priority(&self) -> Option<i32> {
+        match unsafe { j::jack_client_real_time_priority(self.raw()) } {
+            -1 => None,
+            priority => Some(priority),
+        }
+    }
+
+    /// The maximum priority that a thread created by the client may use, or `None` if JACK is not
+    /// running with real-time scheduling.
+    pub fn max_real_time_priority(&self) -> Option<i32> {
+        match unsafe { j::jack_client_max_real_time_priority(self.raw()) } {
+            -1 => None,
+            priority => Some(priority),
+        }
+    }
+
+    /// Spawn a thread that runs `f`, scheduled like the threads that JACK creates for this client.
+    ///
+    /// If JACK is running with real-time scheduling, the thread uses real-time scheduling with
+    /// `priority`. This is usually [`Client::real_time_priority`], or lower for work that should
+    /// not preempt the process thread. Otherwise, `priority` is ignored.
+    ///
+    /// The thread may change its own scheduling with [`promote_current_thread`] and
+    /// [`demote_current_thread`].
+    ///
+    /// `Err(Error::ThreadCreationError)` is returned if the thread could not be created.
+    ///
+    /// [`promote_current_thread`]: crate::promote_current_thread
+    /// [`demote_current_thread`]: crate::demote_current_thread
+    pub fn spawn_realtime_thread<F, T>(
+        &self,
+        priority: i32,
+        f: F,
+    ) -> Result<RealtimeJoinHandle<T>, Error>
+    where
+        F: 'static + Send + FnOnce() -> T,
+        T: 'static + Send,
+    {
+        unsafe {
+            thread::spawn(
+                self.raw(),
+                Arc::downgrade(&self.1),
+                priority,
+                self.is_realtime(),
+                f,
+            )
+        }
+    }
 
     /// Establish a connection between two ports by their full name.
     ///
@@ -564,6 +698,40 @@ impl Client {
         }
     }
 
+    /// Tie `input` to `output` to declare that data is passed through from the input to the output
+    /// without any delay. JACK uses this to process the output port with the data of the input
+    /// port, even if this client is not run, e.g. while it is bypassed.
+    ///
+    /// Both ports must be registered by this client. This is checked when the ports are tied, not
+    /// at compile time: a [`Port`] does not carry the client that registered it in its type, and
+    /// adding one would change the type of every port. `Err(Error::PortTieError)` is returned if
+    /// the ports belong to another client, or if JACK fails to tie them.
+    ///
+    /// The returned [`PortTie`] borrows both ports, so they can not be unregistered before they are
+    /// untied:
+    ///
+    /// ```compile_fail
+    /// let (client, _) = jack::Client::new("tie", jack::ClientOptions::default()).unwrap();
+    /// let input = client.register_port("in", jack::AudioIn::default()).unwrap();
+    /// let output = client.register_port("out", jack::AudioOut::default()).unwrap();
+    /// let tie = client.tie_ports(&input, &output).unwrap();
+    /// client.unregister_port(output).unwrap();
+    /// tie.untie().unwrap();
+    /// ```
+    pub fn tie_ports<'a>(
+        &self,
+        input: &'a Port<AudioIn>,
+        output: &'a Port<AudioOut>,
+    ) -> Result<PortTie<'a>, Error> {
+        if input.client_ptr() != self.raw() || output.client_ptr() != self.raw() {
+            return Err(Error::PortTieError);
+        }
+        match unsafe { j::jack_port_tie(input.raw(), output.raw()) } {
+            0 => Ok(PortTie::new(input, output)),
+            _ => Err(Error::PortTieError),
+        }
+    }
+
     pub fn unregister_port<PS>(&self, port: Port<PS>) -> Result<(), Error> {
         let res = unsafe { j::jack_port_unregister(self.raw(), port.raw()) };
         match res {
@@ -778,6 +946,93 @@ impl ProcessScope {
     }
 }
 
+/// Waits for the process cycles of a [`ProcessThreadHandler`].
+///
+/// This is only available within [`ProcessThreadHandler::run`].
+#[derive(Debug)]
+pub struct ProcessCycles {
+    client_ptr: *mut j::jack_client_t,
+    state: *const ProcessThreadState,
+}
+
+impl ProcessCycles {
+    /// Block until JACK starts the next process cycle.
+    ///
+    /// Returns `None` if the client should stop processing, in which case the handler should
+    /// return from `run`.
+    pub fn wait(&mut self) -> Option<ProcessCycle<'_>> {
+        let state = unsafe { &*self.state };
+        if state.is_stop_requested() || state.is_quit_requested() {
+            return None;
+        }
+        let n_frames = unsafe { j::jack_cycle_wait(self.client_ptr) };
+        if n_frames == 0 {
+            return None;
+        }
+        if state.is_stop_requested() {
+            // The cycle has started, so it must be finished for the rest of the graph to run.
+            unsafe { j::jack_cycle_signal(self.client_ptr, Control::Continue.to_ffi()) };
+            return None;
+        }
+        Some(ProcessCycle {
+            scope: unsafe { ProcessScope::from_raw(n_frames, self.client_ptr) },
+            state,
+            _cycles: std::marker::PhantomData,
+        })
+    }
+
+    /// Create `ProcessCycles` for the client with the given pointer.
+    ///
+    /// # Safety
+    /// Waiting for cycles is only valid within the process thread of an active client, and `state`
+    /// must outlive the `ProcessCycles`.
+    pub(crate) unsafe fn from_raw(
+        client_ptr: *mut j::jack_client_t,
+        state: *const ProcessThreadState,
+    ) -> Self {
+        ProcessCycles { client_ptr, state }
+    }
+}
+
+/// A process cycle started by [`ProcessCycles::wait`].
+///
+/// Dereferences to the [`ProcessScope`] of the cycle, so it can be used to access port buffers. The
+/// cycle ends with [`ProcessCycle::signal`], or with `Control::Continue` when it is dropped.
+#[derive(Debug)]
+pub struct ProcessCycle<'a> {
+    scope: ProcessScope,
+    state: &'a ProcessThreadState,
+    _cycles: std::marker::PhantomData<&'a mut ProcessCycles>,
+}
+
+impl ProcessCycle<'_> {
+    /// End the cycle and allow the clients connected to this one to run.
+    ///
+    /// `Control::Quit` stops processing for this client: the following [`ProcessCycles::wait`]
+    /// returns `None`, and JACK stops running the client once the handler has returned from
+    /// `run`.
+    pub fn signal(self, control: Control) {
+        if control == Control::Quit {
+            self.state.request_quit();
+        }
+        // Dropping the cycle ends it.
+    }
+}
+
+impl std::ops::Deref for ProcessCycle<'_> {
+    type Target = ProcessScope;
+
+    fn deref(&self) -> &ProcessScope {
+        &self.scope
+    }
+}
+
+impl Drop for ProcessCycle<'_> {
+    fn drop(&mut self) {
+        unsafe { j::jack_cycle_signal(self.scope.client_ptr(), Control::Continue.to_ffi()) };
+    }
+}
+
 /// Internal cycle timing information.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CycleTimes {
@@ -3,6 +3,7 @@ mod callbacks;
 mod client_impl;
 mod common;
 mod handler_impls;
+mod thread;
 
 /// Contains `ClientOptions` flags used when opening a client.
 mod client_options;
@@ -11,11 +12,18 @@ mod client_options;
 mod client_status;
 
 pub use self::async_client::AsyncClient;
-pub use self::callbacks::{NotificationHandler, ProcessHandler};
-pub use self::client_impl::{Client, CycleTimes, InternalClientID, ProcessScope};
+pub use self::callbacks::{
+    NotificationHandler, ProcessHandler, ProcessThread, ProcessThreadHandler, TimebaseHandler,
+};
+pub use self::client_impl::{
+    Client, CycleTimes, InternalClientID, ProcessCycle, ProcessCycles, ProcessScope,
+};
 pub use self::client_options::ClientOptions;
 pub use self::client_status::ClientStatus;
 pub use self::common::CLIENT_NAME_SIZE;
+pub use self::thread::RealtimeJoinHandle;
+#[cfg(not(target_os = "windows"))]
+pub use self::thread::{demote_current_thread, promote_current_thread};
 
 #[allow(deprecated)]
 pub use self::handler_impls::ClosureProcessHandler;
@@ -0,0 +1,171 @@
+use jack_sys as j;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Weak};
+use std::{fmt, ptr};
+
+use crate::Error;
+
+type ThreadResult<T> = Arc<Mutex<Option<std::thread::Result<T>>>>;
+
+/// A handle to a thread created by
+/// [`Client::spawn_realtime_thread`](crate::Client::spawn_realtime_thread).
+///
+/// JACK can not detach threads, so the thread must be joined with [`RealtimeJoinHandle::join`]
+/// before its client is closed. If the handle is dropped instead, the thread keeps running but its
+/// resources are only released when the process exits.
+#[must_use = "The thread must be joined to release its resources."]
+pub struct RealtimeJoinHandle<T> {
+    thread: j::jack_native_thread_t,
+    client_ptr: *mut j::jack_client_t,
+    client_life: Weak<()>,
+    result: ThreadResult<T>,
+}
+
+unsafe impl<T: Send> Send for RealtimeJoinHandle<T> {}
+unsafe impl<T: Send> Sync for RealtimeJoinHandle<T> {}
+
+impl<T> RealtimeJoinHandle<T> {
+    /// Wait for the thread to finish and return the value returned by its closure.
+    ///
+    /// `Err(Error::ThreadPanicked)` is returned if the closure panicked,
+    /// `Err(Error::ClientIsNoLongerAlive)` if the client that created the thread was closed, and
+    /// `Err(Error::ThreadJoinError)` if the thread could not be joined.
+    pub fn join(self) -> Result<T, Error> {
+        let _life = self
+            .client_life
+            .upgrade()
+            .ok_or(Error::ClientIsNoLongerAlive)?;
+        if unsafe { j::jack_client_stop_thread(self.client_ptr, self.thread) } != 0 {
+            return Err(Error::ThreadJoinError);
+        }
+        match self.result.lock().ok().and_then(|mut r| r.take()) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(_)) => Err(Error::ThreadPanicked),
+            None => Err(Error::ThreadJoinError),
+        }
+    }
+
+    /// Enable real-time scheduling with `priority` for the thread.
+    ///
+    /// `Err(Error::RealtimeSchedulingError)` is returned on failure, usually because the process
+    /// lacks the permissions to use real-time scheduling.
+    pub fn promote(&self, priority: i32) -> Result<(), Error> {
+        promote_thread(self.thread, priority)
+    }
+
+    /// Drop real-time scheduling for the thread.
+    pub fn demote(&self) -> Result<(), Error> {
+        demote_thread(self.thread)
+    }
+
+    /// Get the underlying native thread handle.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    pub fn raw(&self) -> j::jack_native_thread_t {
+        self.thread
+    }
+}
+
+impl<T> fmt::Debug for RealtimeJoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RealtimeJoinHandle")
+            .field("thread", &self.thread)
+            .finish()
+    }
+}
+
+/// Enable real-time scheduling with `priority` for the calling thread.
+///
+/// Usually called from a thread spawned with
+/// [`Client::spawn_realtime_thread`](crate::Client::spawn_realtime_thread), with the priority
+/// returned by [`Client::real_time_priority`](crate::Client::real_time_priority). Not available on
+/// Windows, where JACK threads are not pthreads.
+///
+/// `Err(Error::RealtimeSchedulingError)` is returned on failure.
+#[cfg(not(target_os = "windows"))]
+pub fn promote_current_thread(priority: i32) -> Result<(), Error> {
+    promote_thread(unsafe { libc::pthread_self() }, priority)
+}
+
+/// Drop real-time scheduling for the calling thread.
+///
+/// Not available on Windows, where JACK threads are not pthreads.
+///
+/// `Err(Error::RealtimeSchedulingError)` is returned on failure.
+#[cfg(not(target_os = "windows"))]
+pub fn demote_current_thread() -> Result<(), Error> {
+    demote_thread(unsafe { libc::pthread_self() })
+}
+
+fn promote_thread(thread: j::jack_native_thread_t, priority: i32) -> Result<(), Error> {
+    match unsafe { j::jack_acquire_real_time_scheduling(thread, priority) } {
+        0 => Ok(()),
+        _ => Err(Error::RealtimeSchedulingError),
+    }
+}
+
+fn demote_thread(thread: j::jack_native_thread_t) -> Result<(), Error> {
+    match unsafe { j::jack_drop_real_time_scheduling(thread) } {
+        0 => Ok(()),
+        _ => Err(Error::RealtimeSchedulingError),
+    }
+}
+
+struct ThreadStart<F, T> {
+    f: F,
+    result: ThreadResult<T>,
+}
+
+unsafe extern "C" fn thread_start<F, T>(arg: *mut libc::c_void) -> *mut libc::c_void
+where
+    F: FnOnce() -> T,
+{
+    let start = Box::from_raw(arg as *mut ThreadStart<F, T>);
+    let ThreadStart { f, result } = *start;
+    let res = catch_unwind(AssertUnwindSafe(f));
+    if let Ok(mut slot) = result.lock() {
+        *slot = Some(res);
+    }
+    ptr::null_mut()
+}
+
+/// Create a thread with JACK and run `f` on it.
+///
+/// # Safety
+/// `client_ptr` must point to an open client that is kept alive by `client_life`.
+pub(crate) unsafe fn spawn<F, T>(
+    client_ptr: *mut j::jack_client_t,
+    client_life: Weak<()>,
+    priority: i32,
+    realtime: bool,
+    f: F,
+) -> Result<RealtimeJoinHandle<T>, Error>
+where
+    F: 'static + Send + FnOnce() -> T,
+    T: 'static + Send,
+{
+    let result: ThreadResult<T> = Arc::default();
+    let start = Box::into_raw(Box::new(ThreadStart {
+        f,
+        result: result.clone(),
+    }));
+    let mut thread = std::mem::zeroed();
+    let res = j::jack_client_create_thread(
+        client_ptr,
+        &mut thread,
+        priority,
+        libc::c_int::from(realtime),
+        Some(thread_start::<F, T>),
+        start as *mut libc::c_void,
+    );
+    if res != 0 {
+        drop(Box::from_raw(start));
+        return Err(Error::ThreadCreationError);
+    }
+    Ok(RealtimeJoinHandle {
+        thread,
+        client_ptr,
+        client_life,
+        result,
+    })
+}
@@ -0,0 +1,446 @@
+//! Rule based automatic connection of ports.
+//!
+//! [`channel`] creates an [`AutoConnectNotifications`], a `NotificationHandler` that forwards
+//! port registrations and connections, and an [`AutoConnector`], which applies the [`Rule`]s to
+//! them on another thread. JACK does not allow making connections from within notification
+//! callbacks, so the connector must be run on its own thread, see [`AutoConnector::run`]. It runs
+//! until it is stopped through an [`AutoConnectorStop`].
+//!
+//! A [`Rule::Connect`] connects every output port that matches one [`PortMatcher`] to every input
+//! port that matches another one as soon as either of them appears. A [`Rule::Block`] removes
+//! matching connections, no matter who made them, and takes precedence over connect rules.
+//! Connect rules never connect a CV port to an audio port, or vice versa, see `SignalType`.
+//!
+//! ```no_run
+//! use jack::contrib::autoconnect::{self, PortMatcher, Rule};
+//!
+//! let rules = vec![
+//!     Rule::Connect {
+//!         output: PortMatcher::name("^Firefox:output_FL$"),
+//!         input: PortMatcher::name("^system:playback_1$"),
+//!     },
+//!     Rule::Block {
+//!         output: PortMatcher::any(),
+//!         input: PortMatcher::name("^recorder:").with_type("midi"),
+//!     },
+//! ];
+//! let (notifications, mut connector) = autoconnect::channel(rules);
+//! let (client, _status) =
+//!     jack::Client::new("autoconnect", jack::ClientOptions::default()).unwrap();
+//! let active_client = client.activate_async(notifications, ()).unwrap();
+//!
+//! let stop = connector.stop_handle();
+//! std::thread::spawn(move || {
+//!     std::io::stdin().read_line(&mut String::new()).ok();
+//!     stop.stop();
+//! });
+//! // Runs until enter is pressed.
+//! connector.run(active_client.as_client(), |action| println!("{:?}", action));
+//! active_client.deactivate().unwrap();
+//! ```
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel as mpsc_channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::jack_enums::CodeOrMessage;
+use crate::{Client, Connection, Error, NotificationHandler, Port, PortFlags, PortId, Unowned};
+
+/// Selects ports by their name, type, aliases and metadata. A port is selected if it matches all
+/// criteria that are set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PortMatcher {
+    name: Option<String>,
+    port_type: Option<String>,
+    alias: Option<String>,
+    property: Option<(String, String)>,
+}
+
+impl PortMatcher {
+    /// Match all ports.
+    pub fn any() -> PortMatcher {
+        PortMatcher::default()
+    }
+
+    /// Match the ports whose full name matches the regular expression `pattern`. Patterns are
+    /// evaluated by JACK, like the patterns of `Client::ports`.
+    pub fn name(pattern: &str) -> PortMatcher {
+        PortMatcher {
+            name: Some(pattern.to_string()),
+            ..PortMatcher::default()
+        }
+    }
+
+    /// Only match ports whose type matches the regular expression `pattern`, such as `"audio"`
+    /// or `"midi"`.
+    pub fn with_type(mut self, pattern: &str) -> Self {
+        self.port_type = Some(pattern.to_string());
+        self
+    }
+
+    /// Only match ports that have the alias `alias`.
+    pub fn with_alias(mut self, alias: &str) -> Self {
+        self.alias = Some(alias.to_string());
+        self
+    }
+
+    /// Only match ports whose metadata property `key` has the value `value`.
+    pub fn with_property(mut self, key: &str, value: &str) -> Self {
+        self.property = Some((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// The names of all ports that match and have `flags`.
+    fn matching_ports(&self, client: &Client, flags: PortFlags) -> Vec<String> {
+        client
+            .ports(self.name.as_deref(), self.port_type.as_deref(), flags)
+            .into_iter()
+            .filter(|name| {
+                client
+                    .port_by_name(name)
+                    .is_some_and(|port| self.matches_details(client, &port))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the port named `name` matches and has `flags`.
+    fn matches(&self, client: &Client, name: &str, flags: PortFlags) -> bool {
+        let port = match client.port_by_name(name) {
+            Some(port) => port,
+            None => return false,
+        };
+        if !port.flags().contains(flags) || !self.matches_details(client, &port) {
+            return false;
+        }
+        if self.name.is_none() && self.port_type.is_none() {
+            return true;
+        }
+        client
+            .ports(self.name.as_deref(), self.port_type.as_deref(), flags)
+            .iter()
+            .any(|port| port == name)
+    }
+
+    /// Check the criteria that JACK can not check.
+    fn matches_details(&self, client: &Client, port: &Port<Unowned>) -> bool {
+        if let Some(alias) = &self.alias {
+            if !port.aliases().is_ok_and(|aliases| aliases.contains(alias)) {
+                return false;
+            }
+        }
+        if let Some((key, value)) = &self.property {
+            let property = port
+                .uuid()
+                .ok()
+                .and_then(|uuid| client.property_get(uuid, key));
+            if property.is_none_or(|property| property.value() != value) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A rule of an [`AutoConnector`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Connect the output ports that match `output` to the input ports that match `input`.
+    Connect {
+        output: PortMatcher,
+        input: PortMatcher,
+    },
+    /// Disconnect the output ports that match `output` from the input ports that match `input`,
+    /// and never connect them.
+    Block {
+        output: PortMatcher,
+        input: PortMatcher,
+    },
+}
+
+/// Something an [`AutoConnector`] did.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Connected(Connection),
+    Disconnected(Connection),
+    /// Connecting or disconnecting failed, for example because the ports are of different types.
+    Failed(Connection, Error),
+}
+
+#[derive(Debug)]
+enum Event {
+    PortRegistered(PortId),
+    PortsConnected(PortId, PortId),
+}
+
+/// Create the notification handler and the connector of an automatic connection daemon.
+pub fn channel(rules: Vec<Rule>) -> (AutoConnectNotifications, AutoConnector) {
+    let (sender, receiver) = mpsc_channel();
+    let notifications = AutoConnectNotifications { events: sender };
+    let connector = AutoConnector {
+        rules,
+        receiver,
+        stop: AutoConnectorStop {
+            is_stopped: Arc::new(AtomicBool::new(false)),
+        },
+    };
+    (notifications, connector)
+}
+
+/// The `NotificationHandler` of an automatic connection daemon. It forwards notifications to the
+/// [`AutoConnector`] without calling the server.
+///
+/// Created with [`channel`].
+#[derive(Debug)]
+pub struct AutoConnectNotifications {
+    events: Sender<Event>,
+}
+
+impl AutoConnectNotifications {
+    fn send(&self, event: Event) {
+        // The connector may have stopped, in which case there is nobody left to notify.
+        let _ = self.events.send(event);
+    }
+}
+
+impl NotificationHandler for AutoConnectNotifications {
+    fn port_registration(&mut self, _: &Client, port_id: PortId, is_registered: bool) {
+        if is_registered {
+            self.send(Event::PortRegistered(port_id));
+        }
+    }
+
+    fn ports_connected(
+        &mut self,
+        _: &Client,
+        port_id_a: PortId,
+        port_id_b: PortId,
+        are_connected: bool,
+    ) {
+        if are_connected {
+            self.send(Event::PortsConnected(port_id_a, port_id_b));
+        }
+    }
+}
+
+/// Applies connection rules to the ports of a server.
+///
+/// Created with [`channel`].
+#[derive(Debug)]
+pub struct AutoConnector {
+    rules: Vec<Rule>,
+    receiver: Receiver<Event>,
+    stop: AutoConnectorStop,
+}
+
+/// Stops [`AutoConnector::run`], possibly from another thread.
+///
+/// Created with [`AutoConnector::stop_handle`].
+#[derive(Clone, Debug)]
+pub struct AutoConnectorStop {
+    is_stopped: Arc<AtomicBool>,
+}
+
+impl AutoConnectorStop {
+    /// Make the connector return from `run` within [`STOP_POLL_INTERVAL`]. A connector that is
+    /// stopped returns from all later calls to `run` right away.
+    pub fn stop(&self) {
+        self.is_stopped.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if [`AutoConnectorStop::stop`] was called.
+    pub fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::Acquire)
+    }
+}
+
+/// How long [`AutoConnector::run`] waits for notifications before it checks if it was stopped.
+pub const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+impl AutoConnector {
+    /// The rules of this connector.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Get a handle that stops [`AutoConnector::run`].
+    pub fn stop_handle(&self) -> AutoConnectorStop {
+        self.stop.clone()
+    }
+
+    /// Apply all rules to all current ports and connections, then apply them to every port and
+    /// connection that appears until the connector is stopped through
+    /// [`AutoConnector::stop_handle`] or the notification handler is dropped. `on_action` is
+    /// called for everything that was done.
+    ///
+    /// `client` should be the client the notification handler is registered with. This blocks,
+    /// so it must not be called from a callback.
+    pub fn run<F: FnMut(&Action)>(&mut self, client: &Client, mut on_action: F) {
+        if self.stop.is_stopped() {
+            return;
+        }
+        for action in self.apply_all(client) {
+            on_action(&action);
+        }
+        while !self.stop.is_stopped() {
+            let actions = match self.process(client, STOP_POLL_INTERVAL) {
+                Some(actions) => actions,
+                None => return,
+            };
+            for action in actions {
+                on_action(&action);
+            }
+        }
+    }
+
+    /// Apply all rules to all current ports and connections.
+    pub fn apply_all(&self, client: &Client) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for rule in &self.rules {
+            if let Rule::Connect { output, input } = rule {
+                let inputs = input.matching_ports(client, PortFlags::IS_INPUT);
+                for output in output.matching_ports(client, PortFlags::IS_OUTPUT) {
+                    for input in &inputs {
+                        self.connect(client, &output, input, &mut actions);
+                    }
+                }
+            }
+        }
+        for output in client.ports(None, None, PortFlags::IS_OUTPUT) {
+            self.enforce_blocks(client, &output, &mut actions);
+        }
+        actions
+    }
+
+    /// Wait up to `timeout` for notifications and apply the rules to the ports and connections
+    /// they report. Returns `None` once the notification handler was dropped.
+    pub fn process(&mut self, client: &Client, timeout: Duration) -> Option<Vec<Action>> {
+        let mut actions = Vec::new();
+        let mut event = match self.receiver.recv_timeout(timeout) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => return Some(actions),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        };
+        loop {
+            match event {
+                Event::PortRegistered(port_id) => {
+                    self.port_registered(client, port_id, &mut actions)
+                }
+                Event::PortsConnected(a, b) => {
+                    for port_id in [a, b] {
+                        let port = client.port_by_id(port_id);
+                        let output = port
+                            .filter(|port| port.flags().contains(PortFlags::IS_OUTPUT))
+                            .and_then(|port| port.name().ok());
+                        if let Some(output) = output {
+                            self.enforce_blocks(client, &output, &mut actions);
+                        }
+                    }
+                }
+            }
+            event = match self.receiver.try_recv() {
+                Ok(event) => event,
+                Err(_) => return Some(actions),
+            };
+        }
+    }
+
+    fn port_registered(&self, client: &Client, port_id: PortId, actions: &mut Vec<Action>) {
+        // The port may be gone again by the time the notification is handled.
+        let port = match client.port_by_id(port_id) {
+            Some(port) => port,
+            None => return,
+        };
+        let (name, flags) = match port.name() {
+            Ok(name) => (name, port.flags()),
+            Err(_) => return,
+        };
+        let is_output = flags.contains(PortFlags::IS_OUTPUT);
+        for rule in &self.rules {
+            if let Rule::Connect { output, input } = rule {
+                if is_output && output.matches(client, &name, PortFlags::IS_OUTPUT) {
+                    for input in input.matching_ports(client, PortFlags::IS_INPUT) {
+                        self.connect(client, &name, &input, actions);
+                    }
+                } else if !is_output && input.matches(client, &name, PortFlags::IS_INPUT) {
+                    for output in output.matching_ports(client, PortFlags::IS_OUTPUT) {
+                        self.connect(client, &output, &name, actions);
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_blocked(&self, client: &Client, output: &str, input: &str) -> bool {
+        self.rules.iter().any(|rule| match rule {
+            Rule::Block {
+                output: output_matcher,
+                input: input_matcher,
+            } => {
+                output_matcher.matches(client, output, PortFlags::IS_OUTPUT)
+                    && input_matcher.matches(client, input, PortFlags::IS_INPUT)
+            }
+            Rule::Connect { .. } => false,
+        })
+    }
+
+    fn connect(&self, client: &Client, output: &str, input: &str, actions: &mut Vec<Action>) {
+        if self.is_blocked(client, output, input) {
+            return;
+        }
+        let (output_port, input_port) =
+            match (client.port_by_name(output), client.port_by_name(input)) {
+                (Some(output_port), Some(input_port)) => (output_port, input_port),
+                _ => return,
+            };
+        if output_port.is_connected_to(input).unwrap_or(false) {
+            return;
+        }
+        let connection = Connection::new(output, input);
+        if mixes_cv_and_audio(&output_port, &input_port) {
+            let err = Error::PortConnectionError {
+                source: output.to_string(),
+                destination: input.to_string(),
+                code_or_message: CodeOrMessage::Message(
+                    "ports have different signal types, CV and audio can not be mixed",
+                ),
+            };
+            actions.push(Action::Failed(connection, err));
+            return;
+        }
+        match client.connect_ports(&output_port, &input_port) {
+            Ok(()) => actions.push(Action::Connected(connection)),
+            Err(Error::PortAlreadyConnected(_, _)) => {}
+            Err(err) => actions.push(Action::Failed(connection, err)),
+        }
+    }
+
+    /// Remove the blocked connections of the output port named `output`.
+    fn enforce_blocks(&self, client: &Client, output: &str, actions: &mut Vec<Action>) {
+        let port = match client.port_by_name(output) {
+            Some(port) => port,
+            None => return,
+        };
+        for input in port.get_connections() {
+            if !self.is_blocked(client, output, &input) {
+                continue;
+            }
+            let connection = Connection::new(output, &input);
+            match client.disconnect_ports_by_name(output, &input) {
+                Ok(()) => actions.push(Action::Disconnected(connection)),
+                Err(err) => actions.push(Action::Failed(connection, err)),
+            }
+        }
+    }
+}
+
+/// Returns `true` if one port is a CV port and the other an audio port. Ports whose signal type
+/// can not be read are not known to mix them.
+fn mixes_cv_and_audio(output: &Port<Unowned>, input: &Port<Unowned>) -> bool {
+    match (
+        output.effective_signal_type(),
+        input.effective_signal_type(),
+    ) {
+        (Ok(Some(output)), Ok(Some(input))) => output != input,
+        _ => false,
+    }
+}
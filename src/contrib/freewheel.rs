@@ -0,0 +1,190 @@
+//! Offline rendering through JACK's freewheel mode.
+//!
+//! In freewheel mode the graph runs as fast as the clients can process it instead of being paced
+//! by the audio interface. This is useful to bounce a session to disk faster than real-time.
+//!
+//! [`FreewheelProcessHandler`] wraps the `ProcessHandler` of an active client and counts the
+//! frames it processes. The paired [`FreewheelRenderer`] enables freewheel mode, waits until a
+//! [`RenderTarget`] is reached, and then restores normal operation. The renderer polls for
+//! progress, so the graph may run a few cycles past the target. Clients that write to disk should
+//! stop writing at the exact frame themselves.
+//!
+//! ```no_run
+//! use jack::contrib::freewheel::{self, RenderTarget};
+//!
+//! let (client, _status) =
+//!     jack::Client::new("bounce", jack::ClientOptions::default()).unwrap();
+//! let handler = jack::contrib::ClosureProcessHandler::new(|_, _| jack::Control::Continue);
+//! let (handler, renderer) = freewheel::wrap(handler);
+//! let active_client = client.activate_async((), handler).unwrap();
+//!
+//! // Render 10 seconds of audio as fast as possible.
+//! let frames = 10 * u64::from(active_client.as_client().sample_rate());
+//! renderer
+//!     .render(active_client.as_client(), RenderTarget::Frames(frames))
+//!     .unwrap();
+//! ```
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{
+    Client, Control, Error, Frames, ProcessHandler, ProcessScope, TransportPosition, TransportState,
+};
+
+/// The default time to sleep between checks of the render progress.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The default time a render may go without processing any frames before it is aborted.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The point at which a freewheel render stops.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// Stop after this many frames have been processed since the render started.
+    Frames(u64),
+    /// Stop once the transport position reaches this frame.
+    TransportFrame(Frames),
+}
+
+/// Create a process handler that counts processed frames and a renderer that observes them.
+pub fn wrap<P: ProcessHandler>(inner: P) -> (FreewheelProcessHandler<P>, FreewheelRenderer) {
+    let processed = Arc::new(AtomicU64::new(0));
+    let handler = FreewheelProcessHandler {
+        inner,
+        processed: processed.clone(),
+    };
+    let renderer = FreewheelRenderer {
+        processed,
+        poll_interval: DEFAULT_POLL_INTERVAL,
+        timeout: DEFAULT_TIMEOUT,
+    };
+    (handler, renderer)
+}
+
+/// A `ProcessHandler` that forwards to `inner` and counts the frames it has processed.
+///
+/// Created with [`wrap`].
+pub struct FreewheelProcessHandler<P> {
+    inner: P,
+    processed: Arc<AtomicU64>,
+}
+
+impl<P> FreewheelProcessHandler<P> {
+    /// The wrapped process handler.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Consume the wrapper and return the wrapped process handler.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P: ProcessHandler> ProcessHandler for FreewheelProcessHandler<P> {
+    const SLOW_SYNC: bool = P::SLOW_SYNC;
+
+    fn process(&mut self, client: &Client, process_scope: &ProcessScope) -> Control {
+        let res = self.inner.process(client, process_scope);
+        self.processed
+            .fetch_add(u64::from(process_scope.n_frames()), Ordering::Release);
+        res
+    }
+
+    fn buffer_size(&mut self, client: &Client, size: Frames) -> Control {
+        self.inner.buffer_size(client, size)
+    }
+
+    fn sync(&mut self, client: &Client, state: TransportState, pos: &TransportPosition) -> bool {
+        self.inner.sync(client, state, pos)
+    }
+}
+
+/// Drives freewheel renders of the client whose process handler was created alongside it.
+///
+/// Created with [`wrap`].
+#[derive(Clone, Debug)]
+pub struct FreewheelRenderer {
+    processed: Arc<AtomicU64>,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl FreewheelRenderer {
+    /// Set how long to sleep between checks of the render progress. Defaults to
+    /// [`DEFAULT_POLL_INTERVAL`].
+    ///
+    /// The graph keeps running while the renderer sleeps, so a render may overshoot its target by
+    /// however many cycles are processed within one interval.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set how long a render may go without the wrapped handler processing any frames before it
+    /// is aborted. Defaults to [`DEFAULT_TIMEOUT`].
+    ///
+    /// This ends renders whose process handler returned `Control::Quit` or whose client was shut
+    /// down.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The total number of frames processed by the wrapped handler.
+    pub fn frames_processed(&self) -> u64 {
+        self.processed.load(Ordering::Acquire)
+    }
+
+    /// Run the graph in freewheel mode until `target` is reached, then return to normal mode.
+    ///
+    /// `client` must be the active client that owns the wrapped process handler. This blocks the
+    /// calling thread and must not be called from a JACK callback. Returns the number of frames
+    /// processed while freewheeling. Normal mode is restored even if checking the target fails.
+    ///
+    /// `Err(Error::FreewheelTimeout)` is returned if no frames were processed for the duration
+    /// set with [`FreewheelRenderer::with_timeout`]. For `RenderTarget::TransportFrame`,
+    /// `Err(Error::TransportNotRolling)` is returned if the transport is stopped before the target
+    /// is reached.
+    pub fn render(&self, client: &Client, target: RenderTarget) -> Result<u64, Error> {
+        let start = self.frames_processed();
+        client.set_freewheel(true)?;
+        let res = self.wait_for(client, start, target);
+        let stop_res = client.set_freewheel(false);
+        let frames = self.frames_processed() - start;
+        res.and(stop_res).map(|_| frames)
+    }
+
+    fn wait_for(&self, client: &Client, start: u64, target: RenderTarget) -> Result<(), Error> {
+        let mut processed = self.frames_processed();
+        let mut last_progress = Instant::now();
+        while !self.is_reached(client, start, target)? {
+            let now_processed = self.frames_processed();
+            if now_processed != processed {
+                processed = now_processed;
+                last_progress = Instant::now();
+            } else if last_progress.elapsed() >= self.timeout {
+                return Err(Error::FreewheelTimeout);
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+        Ok(())
+    }
+
+    fn is_reached(&self, client: &Client, start: u64, target: RenderTarget) -> Result<bool, Error> {
+        match target {
+            RenderTarget::Frames(n) => Ok(self.frames_processed() - start >= n),
+            RenderTarget::TransportFrame(frame) => {
+                let transport = client.transport().query()?;
+                if transport.pos.frame() >= frame {
+                    Ok(true)
+                } else if transport.state == TransportState::Stopped {
+                    Err(Error::TransportNotRolling)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    }
+}
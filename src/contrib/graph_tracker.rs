@@ -0,0 +1,318 @@
+//! A local copy of the JACK graph that is kept up to date by JACK's notifications.
+//!
+//! [`GraphTracker`] takes a [`Graph`] snapshot once and then applies every client registration,
+//! port registration, port rename and connection it is notified of, publishing each of them as a
+//! [`GraphChange`]. The notifications only carry `PortId`s, so the tracker remembers the name of
+//! every port it has seen, as a port can no longer be looked up once it is unregistered.
+//!
+//! Looking up the uuid of a client asks the server, which is not done in the notification
+//! callbacks. Clients that were added since the tracker was loaded have no uuid or pretty name
+//! until [`GraphTracker::resolve_clients`] is called.
+//!
+//! The tracker is cheap to clone and all clones share the same data. A clone must be registered
+//! as the `NotificationHandler` of the client.
+//!
+//! ```no_run
+//! use jack::contrib::graph_tracker::GraphTracker;
+//!
+//! let (client, _status) =
+//!     jack::Client::new("graph_tracker", jack::ClientOptions::default()).unwrap();
+//! let tracker = GraphTracker::new(&client);
+//! let changes = tracker.subscribe();
+//! let _active_client = client.activate_async(tracker.clone(), ()).unwrap();
+//!
+//! for change in changes {
+//!     println!("{:?}", change);
+//! }
+//! ```
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::contrib::lock::lock;
+use crate::{
+    Client, Connection, Control, Graph, GraphClient, GraphPort, NotificationHandler, PortFlags,
+    PortId,
+};
+
+/// A change to the graph of a [`GraphTracker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphChange {
+    ClientAdded(String),
+    ClientRemoved(String),
+    PortAdded(String),
+    PortRemoved(String),
+    PortRenamed { old_name: String, new_name: String },
+    Connected(Connection),
+    Disconnected(Connection),
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    graph: Graph,
+    /// The names of the ports that were seen since the tracker was created.
+    names: HashMap<PortId, String>,
+    subscribers: Vec<Sender<GraphChange>>,
+}
+
+impl TrackerState {
+    fn notify(&mut self, change: GraphChange) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(change.clone()).is_ok());
+    }
+
+    /// The name of the port with `port_id`, looked up while the port is still registered.
+    fn resolve(&mut self, client: &Client, port_id: PortId) -> Option<String> {
+        if let Some(name) = self.names.get(&port_id) {
+            return Some(name.clone());
+        }
+        let name = client.port_by_id(port_id)?.name().ok()?;
+        self.names.insert(port_id, name.clone());
+        Some(name)
+    }
+
+    fn add_client(&mut self, name: &str) {
+        if !self.graph.clients.contains_key(name) {
+            let graph_client = GraphClient {
+                name: name.to_string(),
+                uuid: None,
+                pretty_name: None,
+            };
+            self.graph.clients.insert(name.to_string(), graph_client);
+            self.notify(GraphChange::ClientAdded(name.to_string()));
+        }
+    }
+
+    fn remove_client(&mut self, name: &str) {
+        let ports: Vec<String> = self
+            .graph
+            .client_ports(name)
+            .map(|port| port.name.clone())
+            .collect();
+        for port in ports {
+            self.remove_port(&port);
+        }
+        if self.graph.clients.remove(name).is_some() {
+            self.notify(GraphChange::ClientRemoved(name.to_string()));
+        }
+    }
+
+    fn add_port(&mut self, port: GraphPort) {
+        self.add_client(&port.client.clone());
+        let name = port.name.clone();
+        if self.graph.ports.insert(name.clone(), port).is_none() {
+            self.notify(GraphChange::PortAdded(name));
+        }
+    }
+
+    fn remove_port(&mut self, name: &str) {
+        let connections: Vec<Connection> = self.graph.port_connections(name).cloned().collect();
+        for connection in connections {
+            self.graph.connections.remove(&connection);
+            self.notify(GraphChange::Disconnected(connection));
+        }
+        if self.graph.ports.remove(name).is_some() {
+            self.notify(GraphChange::PortRemoved(name.to_string()));
+        }
+    }
+
+    fn rename_port(&mut self, old_name: &str, new_name: &str) {
+        let mut port = match self.graph.ports.remove(old_name) {
+            Some(port) => port,
+            None => return,
+        };
+        port.name = new_name.to_string();
+        self.graph.ports.insert(new_name.to_string(), port);
+        let connections: Vec<Connection> = self.graph.port_connections(old_name).cloned().collect();
+        for mut connection in connections {
+            self.graph.connections.remove(&connection);
+            for end in [&mut connection.output, &mut connection.input] {
+                if *end == old_name {
+                    *end = new_name.to_string();
+                }
+            }
+            self.graph.connections.insert(connection);
+        }
+        for name in self.names.values_mut() {
+            if name == old_name {
+                *name = new_name.to_string();
+            }
+        }
+        self.notify(GraphChange::PortRenamed {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        });
+    }
+
+    fn is_output(&self, client: &Client, name: &str) -> bool {
+        let flags = match self.graph.ports.get(name) {
+            Some(port) => Some(port.flags),
+            None => client.port_by_name(name).map(|port| port.flags()),
+        };
+        flags.is_some_and(|flags| flags.contains(PortFlags::IS_OUTPUT))
+    }
+
+    /// Remove the ports that are no longer registered. Used for ports that were unregistered
+    /// before their name was known.
+    fn remove_unregistered_ports(&mut self, client: &Client) {
+        let registered = client.ports(None, None, PortFlags::empty());
+        let unregistered: Vec<String> = self
+            .graph
+            .ports
+            .keys()
+            .filter(|name| !registered.contains(name))
+            .cloned()
+            .collect();
+        for name in unregistered {
+            self.remove_port(&name);
+        }
+    }
+}
+
+/// A graph of clients, ports and connections, kept in sync through notifications.
+#[derive(Clone, Debug, Default)]
+pub struct GraphTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl GraphTracker {
+    /// Create a tracker that holds the current graph of the server.
+    ///
+    /// Changes are only tracked once the tracker is registered as the notification handler of
+    /// `client` and `client` is activated, see the [module documentation](self).
+    pub fn new(client: &Client) -> GraphTracker {
+        let tracker = GraphTracker::default();
+        tracker.reload(client);
+        tracker
+    }
+
+    /// Replace the tracked graph with a new snapshot of the server.
+    ///
+    /// Subscribers are not notified of the differences.
+    pub fn reload(&self, client: &Client) {
+        let graph = client.graph();
+        let names = graph
+            .ports
+            .values()
+            .map(|port| (port.uuid.to_index(), port.name.clone()))
+            .collect();
+        let mut state = self.lock();
+        state.graph = graph;
+        state.names = names;
+    }
+
+    /// Look up the uuids and pretty names of the clients that were added since the tracker was
+    /// loaded.
+    ///
+    /// This asks the server and must not be called from a JACK callback. Subscribers are not
+    /// notified.
+    pub fn resolve_clients(&self, client: &Client) {
+        let unresolved: Vec<String> = self
+            .lock()
+            .graph
+            .clients
+            .values()
+            .filter(|graph_client| graph_client.uuid.is_none())
+            .map(|graph_client| graph_client.name.clone())
+            .collect();
+        let resolved: Vec<GraphClient> = unresolved
+            .iter()
+            .map(|name| GraphClient::new(client, name))
+            .collect();
+        let mut state = self.lock();
+        for graph_client in resolved {
+            if let Some(tracked) = state.graph.clients.get_mut(&graph_client.name) {
+                *tracked = graph_client;
+            }
+        }
+    }
+
+    /// Get a copy of the tracked graph.
+    pub fn graph(&self) -> Graph {
+        self.lock().graph.clone()
+    }
+
+    /// Get a receiver for every change that is applied to the graph.
+    ///
+    /// Removing a port sends a `GraphChange::Disconnected` for each of its remaining connections,
+    /// and removing a client a `GraphChange::PortRemoved` for each of its remaining ports. The
+    /// subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<GraphChange> {
+        let (sender, receiver) = channel();
+        self.lock().subscribers.push(sender);
+        receiver
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TrackerState> {
+        lock(&self.state)
+    }
+}
+
+impl NotificationHandler for GraphTracker {
+    fn client_registration(&mut self, _: &Client, name: &str, is_registered: bool) {
+        let mut state = self.lock();
+        if is_registered {
+            state.add_client(name);
+        } else {
+            state.remove_client(name);
+        }
+    }
+
+    fn port_registration(&mut self, client: &Client, port_id: PortId, is_registered: bool) {
+        let mut state = self.lock();
+        if is_registered {
+            let port = client
+                .port_by_id(port_id)
+                .and_then(|port| GraphPort::new(&port));
+            if let Some(port) = port {
+                state.names.insert(port_id, port.name.clone());
+                state.add_port(port);
+            }
+        } else {
+            match state.names.remove(&port_id) {
+                Some(name) => state.remove_port(&name),
+                None => state.remove_unregistered_ports(client),
+            }
+        }
+    }
+
+    fn port_rename(
+        &mut self,
+        _: &Client,
+        _port_id: PortId,
+        old_name: &str,
+        new_name: &str,
+    ) -> Control {
+        self.lock().rename_port(old_name, new_name);
+        Control::Continue
+    }
+
+    fn ports_connected(
+        &mut self,
+        client: &Client,
+        port_id_a: PortId,
+        port_id_b: PortId,
+        are_connected: bool,
+    ) {
+        let mut state = self.lock();
+        let (a, b) = match (
+            state.resolve(client, port_id_a),
+            state.resolve(client, port_id_b),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let connection = if state.is_output(client, &a) {
+            Connection::new(&a, &b)
+        } else {
+            Connection::new(&b, &a)
+        };
+        if are_connected {
+            if state.graph.connections.insert(connection.clone()) {
+                state.notify(GraphChange::Connected(connection));
+            }
+        } else if state.graph.connections.remove(&connection) {
+            state.notify(GraphChange::Disconnected(connection));
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// Lock `mutex`, even if another thread panicked while holding it.
+///
+/// The state shared by the contrib handlers is updated in single steps that can not panic
+/// halfway, so it is always valid and a poisoned lock can safely be ignored.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
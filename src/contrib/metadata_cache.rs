@@ -0,0 +1,241 @@
+//! A local copy of the JACK metadata that is kept up to date by JACK's notifications.
+//!
+//! Reading properties from the server with `Client::property_get_all` is slow and the result is
+//! stale as soon as it is returned. [`MetadataCache`] loads all properties once and then applies
+//! every property change it is notified of, so lookups never have to ask the server. Properties
+//! of clients and ports that are unregistered are evicted. The uuids of clients and ports are
+//! remembered while they are registered, as they can no longer be looked up once they are gone.
+//!
+//! Looking up the uuid of a client asks the server, which is not done in the notification
+//! callbacks. The properties of a client that registered after the cache was loaded are only
+//! evicted if [`MetadataCache::resolve_clients`] was called while it was registered.
+//!
+//! The cache is cheap to clone and all clones share the same data. One clone must be registered
+//! as the `PropertyChangeHandler` and another one as the `NotificationHandler` of the client.
+//!
+//! ```no_run
+//! use jack::contrib::metadata_cache::MetadataCache;
+//!
+//! let (mut client, _status) =
+//!     jack::Client::new("metadata_cache", jack::ClientOptions::default()).unwrap();
+//! let cache = MetadataCache::new(&client);
+//! client
+//!     .register_property_change_handler(cache.clone())
+//!     .unwrap();
+//! let changes = cache.subscribe();
+//! let _active_client = client.activate_async(cache.clone(), ()).unwrap();
+//!
+//! for change in changes {
+//!     println!("{:?}", change);
+//! }
+//! ```
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::contrib::lock::lock;
+use crate::properties::get_property;
+use crate::{
+    Client, NotificationHandler, PortFlags, PortId, Property, PropertyChange,
+    PropertyChangeHandler, PropertyChangeOwned, PropertyMap, Uuid,
+};
+
+#[derive(Debug, Default)]
+struct CacheState {
+    properties: HashMap<Uuid, PropertyMap>,
+    /// The uuids of the registered ports.
+    ports: HashMap<PortId, Uuid>,
+    /// The uuids of the registered clients, by name. `None` until the uuid is looked up.
+    clients: HashMap<String, Option<Uuid>>,
+    subscribers: Vec<Sender<PropertyChangeOwned>>,
+}
+
+impl CacheState {
+    fn notify(&mut self, change: PropertyChangeOwned) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(change.clone()).is_ok());
+    }
+
+    fn update(&mut self, subject: Uuid, key: &str, change: PropertyChangeOwned) {
+        match get_property(subject, key) {
+            Some(property) => {
+                self.properties
+                    .entry(subject)
+                    .or_default()
+                    .insert(key.to_string(), property);
+            }
+            None => self.remove(subject, key),
+        }
+        self.notify(change);
+    }
+
+    fn remove(&mut self, subject: Uuid, key: &str) {
+        if let Some(map) = self.properties.get_mut(&subject) {
+            map.remove(key);
+            if map.is_empty() {
+                self.properties.remove(&subject);
+            }
+        }
+    }
+
+    fn remove_subject(&mut self, subject: Uuid) {
+        if let Some(map) = self.properties.remove(&subject) {
+            for key in map.into_keys() {
+                self.notify(PropertyChangeOwned::Deleted { subject, key });
+            }
+        }
+    }
+}
+
+/// A cache of all JACK metadata, kept in sync through property change and registration
+/// notifications.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl MetadataCache {
+    /// Create a cache that holds all properties currently known to the server.
+    ///
+    /// Changes are only tracked once the cache is registered with `client` and `client` is
+    /// activated, see the [module documentation](self).
+    pub fn new(client: &Client) -> MetadataCache {
+        let cache = MetadataCache::default();
+        cache.reload(client);
+        cache
+    }
+
+    /// Replace the cached properties with the ones currently known to the server.
+    ///
+    /// Subscribers are not notified of the differences.
+    pub fn reload(&self, client: &Client) {
+        let properties = client.property_get_all();
+        let mut ports = HashMap::new();
+        let mut clients = HashMap::new();
+        for name in client.ports(None, None, PortFlags::empty()) {
+            if let Some(uuid) = client.port_by_name(&name).and_then(|port| port.uuid().ok()) {
+                ports.insert(uuid.to_index(), uuid);
+            }
+            if let Some((client_name, _)) = name.split_once(':') {
+                if !clients.contains_key(client_name) {
+                    let uuid = client.uuid_of_client_by_name(client_name);
+                    clients.insert(client_name.to_string(), uuid);
+                }
+            }
+        }
+        clients.insert(client.name().to_string(), Some(client.uuid()));
+        let mut state = self.lock();
+        state.properties = properties;
+        state.ports = ports;
+        state.clients = clients;
+    }
+
+    /// Look up the uuids of the clients that registered since the cache was loaded, so their
+    /// properties can be evicted once they are unregistered.
+    ///
+    /// This asks the server and must not be called from a JACK callback.
+    pub fn resolve_clients(&self, client: &Client) {
+        let unresolved: Vec<String> = self
+            .lock()
+            .clients
+            .iter()
+            .filter(|(_, uuid)| uuid.is_none())
+            .map(|(name, _)| name.clone())
+            .collect();
+        let resolved: Vec<(String, Option<Uuid>)> = unresolved
+            .into_iter()
+            .map(|name| {
+                let uuid = client.uuid_of_client_by_name(&name);
+                (name, uuid)
+            })
+            .collect();
+        let mut state = self.lock();
+        for (name, uuid) in resolved {
+            // The client may have been unregistered in the meantime.
+            if let Some(tracked) = state.clients.get_mut(&name) {
+                *tracked = uuid;
+            }
+        }
+    }
+
+    /// Get a cached property.
+    pub fn property(&self, subject: Uuid, key: &str) -> Option<Property> {
+        self.lock()
+            .properties
+            .get(&subject)
+            .and_then(|map| map.get(key))
+            .cloned()
+    }
+
+    /// Get all the cached properties of `subject`. `None` if `subject` has no properties.
+    pub fn subject(&self, subject: Uuid) -> Option<PropertyMap> {
+        self.lock().properties.get(&subject).cloned()
+    }
+
+    /// Get all subjects that have properties.
+    pub fn subjects(&self) -> Vec<Uuid> {
+        self.lock().properties.keys().copied().collect()
+    }
+
+    /// Get a receiver for every change that is applied to the cache.
+    ///
+    /// Evicting a subject sends a `PropertyChangeOwned::Deleted` for each of its properties. The
+    /// subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<PropertyChangeOwned> {
+        let (sender, receiver) = channel();
+        self.lock().subscribers.push(sender);
+        receiver
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        lock(&self.state)
+    }
+}
+
+impl PropertyChangeHandler for MetadataCache {
+    fn property_changed(&mut self, change: &PropertyChange) {
+        let mut state = self.lock();
+        match *change {
+            PropertyChange::Created { subject, key } | PropertyChange::Changed { subject, key } => {
+                state.update(subject, key, change.into())
+            }
+            // JACK reports the removal of all properties of a subject with an empty key, and the
+            // removal of all properties with an empty subject.
+            PropertyChange::Deleted { subject, key: "" } if subject.is_empty() => {
+                let subjects: Vec<Uuid> = state.properties.keys().copied().collect();
+                for subject in subjects {
+                    state.remove_subject(subject);
+                }
+            }
+            PropertyChange::Deleted { subject, key: "" } => state.remove_subject(subject),
+            PropertyChange::Deleted { subject, key } => {
+                state.remove(subject, key);
+                state.notify(change.into());
+            }
+        }
+    }
+}
+
+impl NotificationHandler for MetadataCache {
+    fn client_registration(&mut self, _: &Client, name: &str, is_registered: bool) {
+        let mut state = self.lock();
+        if is_registered {
+            state.clients.insert(name.to_string(), None);
+        } else if let Some(uuid) = state.clients.remove(name).flatten() {
+            state.remove_subject(uuid);
+        }
+    }
+
+    fn port_registration(&mut self, client: &Client, port_id: PortId, is_registered: bool) {
+        if is_registered {
+            if let Some(uuid) = client.port_by_id(port_id).and_then(|port| port.uuid().ok()) {
+                self.lock().ports.insert(port_id, uuid);
+            }
+        } else {
+            let mut state = self.lock();
+            if let Some(uuid) = state.ports.remove(&port_id) {
+                state.remove_subject(uuid);
+            }
+        }
+    }
+}
@@ -0,0 +1,766 @@
+//! Midi clock synchronisation with the JACK transport.
+//!
+//! [`MidiClockGenerator`] sends midi clock, Start, Stop, Continue and Song Position Pointer
+//! messages that follow the JACK transport, so external devices can play in sync with it.
+//! [`MidiClockFollower`] does the opposite and drives the JACK transport from the midi clock of an
+//! external device.
+//!
+//! ```no_run
+//! use jack::contrib::midi_clock::MidiClockGenerator;
+//!
+//! let (client, _status) =
+//!     jack::Client::new("midi_clock", jack::ClientOptions::default()).unwrap();
+//! let mut port = client
+//!     .register_port("clock_out", jack::MidiOut::default())
+//!     .unwrap();
+//! let mut generator = MidiClockGenerator::new();
+//! let handler = jack::contrib::ClosureProcessHandler::new(move |client, ps| {
+//!     let mut writer = port.writer(ps);
+//!     generator.process(client, ps, &mut writer).unwrap();
+//!     jack::Control::Continue
+//! });
+//! let _active_client = client.activate_async((), handler).unwrap();
+//! ```
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::contrib::transport_sync::{CycleWriter, NextCycle, TransportCommand};
+use crate::{
+    Client, Error, Frames, MidiIn, MidiMessage, MidiWriter, Port, ProcessScope, TimebaseHandler,
+    TransportBBT, TransportBBTValidationError, TransportPosition, TransportState,
+};
+
+/// The number of midi clocks in a quarter note.
+pub const CLOCKS_PER_QUARTER: u64 = 24;
+
+/// The number of midi clocks in a midi beat, the unit of the Song Position Pointer.
+pub const CLOCKS_PER_MIDI_BEAT: u64 = 6;
+
+/// The tempo that is used if the transport has no BBT data.
+pub const DEFAULT_BPM: f64 = 120.0;
+
+/// The default weight of each new clock interval in the tempo estimate of a
+/// [`MidiClockFollower`].
+pub const DEFAULT_SMOOTHING: f64 = 0.1;
+
+/// The largest position that can be sent in a Song Position Pointer, in midi beats.
+const MAX_SONG_POSITION: u64 = (1 << 14) - 1;
+
+/// Generates midi clock from the JACK transport.
+///
+/// The position and tempo are taken from the BBT data of the transport position. If there is none,
+/// the position is derived from the transport frame at a constant tempo, see
+/// [`MidiClockGenerator::with_default_bpm`].
+///
+/// When the transport starts rolling, a Start is sent at the beginning of the song and a Song
+/// Position Pointer followed by a Continue anywhere else. The Song Position Pointer is sent as soon
+/// as the transport is starting, to give devices time to locate. Clocks are sent from the next
+/// midi beat, the resolution of the Song Position Pointer, onwards. Relocating a rolling transport
+/// sends a Stop and starts over.
+#[derive(Clone, Debug)]
+pub struct MidiClockGenerator {
+    default_bpm: f64,
+    /// The next clock to send, counted from the start of the song. `None` while stopped.
+    next_clock: Option<u64>,
+    next_cycle: NextCycle,
+    /// The transport frame at which a Song Position Pointer was sent while starting.
+    located_frame: Option<Frames>,
+}
+
+impl Default for MidiClockGenerator {
+    fn default() -> Self {
+        MidiClockGenerator {
+            default_bpm: DEFAULT_BPM,
+            next_clock: None,
+            next_cycle: NextCycle::default(),
+            located_frame: None,
+        }
+    }
+}
+
+impl MidiClockGenerator {
+    /// Create a generator that is stopped.
+    pub fn new() -> MidiClockGenerator {
+        MidiClockGenerator::default()
+    }
+
+    /// Set the tempo that is used when the transport has no BBT data. Defaults to
+    /// [`DEFAULT_BPM`].
+    pub fn with_default_bpm(mut self, bpm: f64) -> Self {
+        self.default_bpm = bpm;
+        self
+    }
+
+    /// Returns `true` if the generator has sent a Start or Continue and is sending clocks.
+    pub fn is_running(&self) -> bool {
+        self.next_clock.is_some()
+    }
+
+    /// Write the messages for the current cycle into `writer` and return how many were written.
+    pub fn process(
+        &mut self,
+        client: &Client,
+        ps: &ProcessScope,
+        writer: &mut MidiWriter,
+    ) -> Result<usize, Error> {
+        let transport = client.transport().query()?;
+        let n_frames = ps.n_frames();
+        let window = self.quarter_window(&transport.pos, n_frames, client.sample_rate());
+        let mut writer = CycleWriter::new(writer);
+        self.cycle(
+            transport.state,
+            transport.pos.frame(),
+            n_frames,
+            window,
+            |time, message| writer.write(time, &message),
+        );
+        writer.finish()
+    }
+
+    /// The start and end of the cycle in quarter notes since the start of the song.
+    fn quarter_window(
+        &self,
+        pos: &TransportPosition,
+        n_frames: Frames,
+        sample_rate: u32,
+    ) -> (f64, f64) {
+        let sample_rate = f64::from(sample_rate);
+        match pos.bbt() {
+            Some(bbt) => {
+                let start = bbt.quarter_notes();
+                (start, start + bbt.quarter_notes_in(n_frames, sample_rate))
+            }
+            None => {
+                let quarters_per_frame = self.default_bpm / 60.0 / sample_rate;
+                let start = f64::from(pos.frame()) * quarters_per_frame;
+                (start, start + f64::from(n_frames) * quarters_per_frame)
+            }
+        }
+    }
+
+    /// Emit the messages of a cycle that starts at transport `frame` and spans the quarter notes
+    /// in `window`.
+    fn cycle<F>(
+        &mut self,
+        state: TransportState,
+        frame: Frames,
+        n_frames: Frames,
+        window: (f64, f64),
+        mut emit: F,
+    ) where
+        F: FnMut(Frames, MidiMessage<'static>),
+    {
+        let (start, end) = window;
+        match state {
+            TransportState::Stopped => {
+                self.next_cycle.reset();
+                if self.next_clock.take().is_some() {
+                    emit(0, MidiMessage::Stop);
+                }
+                self.located_frame = None;
+            }
+            TransportState::Starting => {
+                self.next_cycle.reset();
+                if self.next_clock.take().is_some() {
+                    emit(0, MidiMessage::Stop);
+                }
+                if start > 0.0 && self.located_frame != Some(frame) {
+                    emit(
+                        0,
+                        MidiMessage::SongPosition(song_position(first_midi_beat(start))),
+                    );
+                    self.located_frame = Some(frame);
+                }
+            }
+            TransportState::Rolling => {
+                if self.next_cycle.advance(frame, n_frames) {
+                    emit(0, MidiMessage::Stop);
+                    self.next_clock = None;
+                    self.located_frame = None;
+                }
+                let mut clock = match self.next_clock {
+                    Some(clock) => clock,
+                    None => {
+                        let midi_beat = first_midi_beat(start);
+                        if start <= 0.0 {
+                            emit(0, MidiMessage::Start);
+                        } else {
+                            if self.located_frame != Some(frame) {
+                                emit(0, MidiMessage::SongPosition(song_position(midi_beat)));
+                            }
+                            emit(0, MidiMessage::Continue);
+                        }
+                        self.located_frame = None;
+                        midi_beat * CLOCKS_PER_MIDI_BEAT
+                    }
+                };
+                let cycle_quarters = end - start;
+                loop {
+                    let quarters = clock as f64 / CLOCKS_PER_QUARTER as f64;
+                    if cycle_quarters <= 0.0 || quarters >= end {
+                        break;
+                    }
+                    let offset = ((quarters - start).max(0.0) / cycle_quarters
+                        * f64::from(n_frames))
+                    .round() as Frames;
+                    emit(offset.min(n_frames - 1), MidiMessage::Clock);
+                    clock += 1;
+                }
+                self.next_clock = Some(clock);
+            }
+        }
+    }
+}
+
+/// The state a [`MidiClockFollower`] shares with its [`MidiClockTimebase`]. Floats are stored as
+/// their bits.
+#[derive(Debug, Default)]
+struct FollowerState {
+    /// The estimated tempo in quarter notes per minute, `0` if unknown.
+    bpm: AtomicU64,
+    /// The position at the start of the next cycle in quarter notes.
+    quarters: AtomicU64,
+}
+
+/// Drives the JACK transport from the midi clock of an external device.
+///
+/// Start, Stop and Continue start and stop the transport, and Start and Song Position Pointer
+/// locate it. The tempo is estimated from the intervals between clocks with an exponential
+/// moving average, see [`MidiClockFollower::with_smoothing`].
+///
+/// The transport frame only advances with the sample rate, so the musical position follows the
+/// clocks through the BBT information published by a [`MidiClockTimebase`]:
+///
+/// ```no_run
+/// use jack::contrib::midi_clock::MidiClockFollower;
+///
+/// let (client, _status) =
+///     jack::Client::new("midi_clock_follower", jack::ClientOptions::default()).unwrap();
+/// let port = client
+///     .register_port("clock_in", jack::MidiIn::default())
+///     .unwrap();
+/// let mut follower = MidiClockFollower::new();
+/// let timebase = follower.timebase();
+/// let handler = jack::contrib::ClosureProcessHandler::new(move |client, ps| {
+///     follower.process(client, ps, &port).unwrap();
+///     jack::Control::Continue
+/// });
+/// let mut active_client = client.activate_async((), handler).unwrap();
+/// active_client.set_timebase_handler(timebase).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MidiClockFollower {
+    smoothing: f64,
+    shared: Arc<FollowerState>,
+    /// The estimated number of frames between clocks.
+    clock_period: Option<f64>,
+    last_clock_frame: Option<Frames>,
+    /// The position of the last clock since the song was located, in clocks.
+    last_clock_position: Option<u64>,
+    /// The position of the next clock, in clocks.
+    next_clock_position: u64,
+    is_running: bool,
+}
+
+impl Default for MidiClockFollower {
+    fn default() -> Self {
+        MidiClockFollower {
+            smoothing: DEFAULT_SMOOTHING,
+            shared: Arc::default(),
+            clock_period: None,
+            last_clock_frame: None,
+            last_clock_position: None,
+            next_clock_position: 0,
+            is_running: false,
+        }
+    }
+}
+
+impl MidiClockFollower {
+    /// Create a follower that has not received any clocks.
+    pub fn new() -> MidiClockFollower {
+        MidiClockFollower::default()
+    }
+
+    /// Set the weight of each new clock interval in the tempo estimate, between `0` and `1`.
+    /// Smaller values give a steadier tempo that is slower to follow tempo changes. Defaults to
+    /// [`DEFAULT_SMOOTHING`].
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(f64::EPSILON, 1.0);
+        self
+    }
+
+    /// Create a timebase handler that publishes the position and tempo of this follower, in 4/4
+    /// time with 1920 ticks per beat.
+    pub fn timebase(&self) -> MidiClockTimebase {
+        MidiClockTimebase {
+            shared: self.shared.clone(),
+            sig_num: 4.0,
+            sig_denom: 4.0,
+            ticks_per_beat: 1920.0,
+        }
+    }
+
+    /// The estimated tempo in quarter notes per minute. `None` until two clocks were received.
+    pub fn bpm(&self) -> Option<f64> {
+        bpm_from_bits(self.shared.bpm.load(Ordering::Relaxed))
+    }
+
+    /// Returns `true` between a Start or Continue and a Stop.
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Handle the midi messages that `port` received in the current cycle and control the
+    /// transport accordingly.
+    pub fn process(
+        &mut self,
+        client: &Client,
+        ps: &ProcessScope,
+        port: &Port<MidiIn>,
+    ) -> Result<(), Error> {
+        let transport = client.transport();
+        let sample_rate = client.sample_rate();
+        let cycle_start = ps.last_frame_time();
+        for event in port.iter(ps) {
+            let message = match MidiMessage::try_from(event) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            let frame = cycle_start.wrapping_add(event.time);
+            if let Some(command) = self.handle(frame, &message, sample_rate) {
+                command.apply(&transport)?;
+            }
+        }
+        self.publish(cycle_start.wrapping_add(ps.n_frames()));
+        Ok(())
+    }
+
+    /// Update the state with `message`, received at `frame`.
+    fn handle(
+        &mut self,
+        frame: Frames,
+        message: &MidiMessage,
+        sample_rate: u32,
+    ) -> Option<TransportCommand> {
+        let sample_rate = f64::from(sample_rate);
+        match *message {
+            MidiMessage::Clock => {
+                if let Some(last_frame) = self.last_clock_frame {
+                    let interval = f64::from(frame.wrapping_sub(last_frame));
+                    // Longer intervals are pauses of the clock rather than a tempo below 5 bpm.
+                    if interval > 0.0 && interval < sample_rate / 2.0 {
+                        let period = match self.clock_period {
+                            Some(period) => period + self.smoothing * (interval - period),
+                            None => interval,
+                        };
+                        self.clock_period = Some(period);
+                        let bpm = 60.0 * sample_rate / (period * CLOCKS_PER_QUARTER as f64);
+                        self.shared.bpm.store(bpm.to_bits(), Ordering::Relaxed);
+                    }
+                }
+                self.last_clock_frame = Some(frame);
+                if self.is_running {
+                    self.last_clock_position = Some(self.next_clock_position);
+                    self.next_clock_position += 1;
+                }
+                None
+            }
+            MidiMessage::Start => {
+                self.locate(0);
+                self.is_running = true;
+                Some(TransportCommand::LocateAndStart(0))
+            }
+            MidiMessage::Continue => {
+                self.is_running = true;
+                Some(TransportCommand::Start)
+            }
+            MidiMessage::Stop => {
+                self.is_running = false;
+                Some(TransportCommand::Stop)
+            }
+            MidiMessage::SongPosition(beats) => {
+                let clocks = u64::from(beats) * CLOCKS_PER_MIDI_BEAT;
+                self.locate(clocks);
+                let quarters = clocks as f64 / CLOCKS_PER_QUARTER as f64;
+                let bpm = self.bpm().unwrap_or(DEFAULT_BPM);
+                let frame = quarters * 60.0 / bpm * sample_rate;
+                Some(TransportCommand::Locate(frame.round() as Frames))
+            }
+            _ => None,
+        }
+    }
+
+    fn locate(&mut self, clocks: u64) {
+        self.last_clock_position = None;
+        self.next_clock_position = clocks;
+    }
+
+    /// The position at `frame` in quarter notes. Between clocks, the position is interpolated
+    /// with the estimated tempo, but it never passes the next clock.
+    fn quarters_at(&self, frame: Frames) -> f64 {
+        let clocks = match (
+            self.last_clock_position,
+            self.last_clock_frame,
+            self.clock_period,
+        ) {
+            (Some(position), Some(last_frame), Some(period)) => {
+                let elapsed = f64::from(frame.wrapping_sub(last_frame)) / period;
+                position as f64 + elapsed.min(1.0)
+            }
+            (Some(position), _, _) => position as f64,
+            (None, _, _) => self.next_clock_position as f64,
+        };
+        clocks / CLOCKS_PER_QUARTER as f64
+    }
+
+    fn publish(&self, next_cycle_start: Frames) {
+        let quarters = self.quarters_at(next_cycle_start);
+        self.shared
+            .quarters
+            .store(quarters.to_bits(), Ordering::Relaxed);
+    }
+}
+
+fn bpm_from_bits(bits: u64) -> Option<f64> {
+    let bpm = f64::from_bits(bits);
+    if bpm > 0.0 {
+        Some(bpm)
+    } else {
+        None
+    }
+}
+
+/// A timebase handler that publishes the position and tempo of a [`MidiClockFollower`] as BBT
+/// information.
+///
+/// Created with [`MidiClockFollower::timebase`] and registered with
+/// `AsyncClient::set_timebase_handler` on the client that runs the follower.
+#[derive(Debug)]
+pub struct MidiClockTimebase {
+    shared: Arc<FollowerState>,
+    sig_num: f32,
+    sig_denom: f32,
+    ticks_per_beat: f64,
+}
+
+impl MidiClockTimebase {
+    /// Set the time signature. Midi clock does not carry one, so it defaults to 4/4.
+    ///
+    /// `Err(TransportBBTValidationError::SigNumRange)` or `SigDenomRange` is returned if
+    /// `sig_num` or `sig_denom` is not greater than zero.
+    pub fn with_time_signature(
+        mut self,
+        sig_num: f32,
+        sig_denom: f32,
+    ) -> Result<Self, TransportBBTValidationError> {
+        if sig_num.is_nan() || sig_num <= 0.0 {
+            return Err(TransportBBTValidationError::SigNumRange);
+        }
+        if sig_denom.is_nan() || sig_denom <= 0.0 {
+            return Err(TransportBBTValidationError::SigDenomRange);
+        }
+        self.sig_num = sig_num;
+        self.sig_denom = sig_denom;
+        Ok(self)
+    }
+
+    /// Set the number of ticks in a beat. Defaults to 1920.
+    ///
+    /// `Err(TransportBBTValidationError::TicksPerBeatRange)` is returned if `ticks_per_beat` is not
+    /// greater than zero.
+    pub fn with_ticks_per_beat(
+        mut self,
+        ticks_per_beat: f64,
+    ) -> Result<Self, TransportBBTValidationError> {
+        if ticks_per_beat.is_nan() || ticks_per_beat <= 0.0 {
+            return Err(TransportBBTValidationError::TicksPerBeatRange);
+        }
+        self.ticks_per_beat = ticks_per_beat;
+        Ok(self)
+    }
+
+    fn bbt(&self) -> TransportBBT {
+        let quarters = f64::from_bits(self.shared.quarters.load(Ordering::Relaxed));
+        let quarter_bpm = bpm_from_bits(self.shared.bpm.load(Ordering::Relaxed));
+        let beats_per_quarter = f64::from(self.sig_denom) / 4.0;
+        let beats_per_bar = f64::from(self.sig_num);
+        let beats = quarters * beats_per_quarter;
+        let bar = (beats / beats_per_bar).floor();
+        let beat = (beats - bar * beats_per_bar).floor();
+        let tick = ((beats - beats.floor()) * self.ticks_per_beat).floor();
+        TransportBBT {
+            bar: bar as usize + 1,
+            beat: beat as usize + 1,
+            tick: (tick as usize).min(self.ticks_per_beat.ceil() as usize - 1),
+            sig_num: self.sig_num,
+            sig_denom: self.sig_denom,
+            ticks_per_beat: self.ticks_per_beat,
+            bpm: quarter_bpm.unwrap_or(DEFAULT_BPM) * beats_per_quarter,
+            bar_start_tick: bar * beats_per_bar * self.ticks_per_beat,
+        }
+    }
+}
+
+impl TimebaseHandler for MidiClockTimebase {
+    fn timebase(
+        &mut self,
+        _: &Client,
+        _state: TransportState,
+        _n_frames: Frames,
+        pos: &mut TransportPosition,
+        _is_new_position: bool,
+    ) {
+        // The follower only publishes valid positions, so this can not fail.
+        pos.set_bbt(Some(self.bbt())).ok();
+    }
+}
+
+/// The first midi beat at or after `quarters`.
+fn first_midi_beat(quarters: f64) -> u64 {
+    (quarters.max(0.0) * (CLOCKS_PER_QUARTER / CLOCKS_PER_MIDI_BEAT) as f64).ceil() as u64
+}
+
+/// The Song Position Pointer value of `midi_beat`. Positions past the last one that can be sent
+/// are clamped to it.
+fn song_position(midi_beat: u64) -> u16 {
+    midi_beat.min(MAX_SONG_POSITION) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(
+        generator: &mut MidiClockGenerator,
+        state: TransportState,
+        frame: Frames,
+        window: (f64, f64),
+    ) -> Vec<(Frames, MidiMessage<'static>)> {
+        let mut messages = Vec::new();
+        generator.cycle(state, frame, 100, window, |time, message| {
+            messages.push((time, message))
+        });
+        messages
+    }
+
+    #[test]
+    fn starting_from_the_beginning_sends_start_and_clocks() {
+        let mut generator = MidiClockGenerator::new();
+        assert_eq!(
+            cycle(&mut generator, TransportState::Stopped, 0, (0.0, 0.1)),
+            []
+        );
+        let messages = cycle(&mut generator, TransportState::Rolling, 0, (0.0, 0.125));
+        assert_eq!(
+            messages,
+            [
+                (0, MidiMessage::Start),
+                (0, MidiMessage::Clock),
+                (33, MidiMessage::Clock),
+                (67, MidiMessage::Clock),
+            ]
+        );
+        assert!(generator.is_running());
+        let messages = cycle(&mut generator, TransportState::Rolling, 100, (0.125, 0.25));
+        assert_eq!(
+            messages,
+            [
+                (0, MidiMessage::Clock),
+                (33, MidiMessage::Clock),
+                (67, MidiMessage::Clock)
+            ]
+        );
+        let messages = cycle(&mut generator, TransportState::Stopped, 200, (0.25, 0.375));
+        assert_eq!(messages, [(0, MidiMessage::Stop)]);
+        assert!(!generator.is_running());
+    }
+
+    #[test]
+    fn tempo_changes_do_not_skip_or_repeat_clocks() {
+        let mut generator = MidiClockGenerator::new();
+        let mut clocks = 0;
+        let windows = [(0.0, 0.1), (0.1, 0.3), (0.3, 0.35), (0.35, 1.0)];
+        for (i, window) in windows.iter().enumerate() {
+            let messages = cycle(
+                &mut generator,
+                TransportState::Rolling,
+                i as Frames * 100,
+                *window,
+            );
+            clocks += messages
+                .iter()
+                .filter(|(_, message)| *message == MidiMessage::Clock)
+                .count();
+        }
+        assert_eq!(clocks, 24);
+    }
+
+    #[test]
+    fn relocation_sends_song_position_and_continue() {
+        let mut generator = MidiClockGenerator::new();
+        cycle(&mut generator, TransportState::Rolling, 0, (0.0, 0.125));
+        // Relocate to the middle of the second midi beat.
+        let messages = cycle(&mut generator, TransportState::Rolling, 5000, (0.3, 0.55));
+        assert_eq!(
+            messages,
+            [
+                (0, MidiMessage::Stop),
+                (0, MidiMessage::SongPosition(2)),
+                (0, MidiMessage::Continue),
+                (80, MidiMessage::Clock),
+                (97, MidiMessage::Clock),
+            ]
+        );
+    }
+
+    #[test]
+    fn song_position_is_sent_once_while_starting() {
+        let mut generator = MidiClockGenerator::new();
+        let starting = cycle(&mut generator, TransportState::Starting, 4800, (1.0, 1.125));
+        assert_eq!(starting, [(0, MidiMessage::SongPosition(4))]);
+        assert_eq!(
+            cycle(&mut generator, TransportState::Starting, 4800, (1.0, 1.125)),
+            []
+        );
+        let rolling = cycle(&mut generator, TransportState::Rolling, 4800, (1.0, 1.125));
+        assert_eq!(
+            rolling,
+            [
+                (0, MidiMessage::Continue),
+                (0, MidiMessage::Clock),
+                (33, MidiMessage::Clock),
+                (67, MidiMessage::Clock),
+            ]
+        );
+    }
+
+    #[test]
+    fn clocks_continue_past_the_last_song_position() {
+        let mut generator = MidiClockGenerator::new();
+        // 5000 quarter notes are 20000 midi beats, more than a Song Position Pointer can hold.
+        let messages = cycle(
+            &mut generator,
+            TransportState::Rolling,
+            0,
+            (5000.0, 5000.125),
+        );
+        assert_eq!(
+            messages,
+            [
+                (0, MidiMessage::SongPosition(16383)),
+                (0, MidiMessage::Continue),
+                (0, MidiMessage::Clock),
+                (33, MidiMessage::Clock),
+                (67, MidiMessage::Clock),
+            ]
+        );
+    }
+
+    fn follow(
+        follower: &mut MidiClockFollower,
+        messages: &[(Frames, MidiMessage)],
+    ) -> Vec<TransportCommand> {
+        messages
+            .iter()
+            .filter_map(|(frame, message)| follower.handle(*frame, message, 48000))
+            .collect()
+    }
+
+    #[test]
+    fn follower_estimates_tempo_from_clocks() {
+        let mut follower = MidiClockFollower::new().with_smoothing(0.5);
+        assert_eq!(follower.bpm(), None);
+        // 1000 frames between clocks at 48 kHz is 120 bpm.
+        let clocks: Vec<_> = (0..10).map(|i| (i * 1000, MidiMessage::Clock)).collect();
+        follow(&mut follower, &clocks);
+        assert_eq!(follower.bpm(), Some(120.0));
+        // A single longer interval only moves the estimate halfway.
+        follow(&mut follower, &[(11000, MidiMessage::Clock)]);
+        assert_eq!(follower.bpm(), Some(80.0));
+        // Pauses are ignored.
+        follow(&mut follower, &[(100_000, MidiMessage::Clock)]);
+        assert_eq!(follower.bpm(), Some(80.0));
+    }
+
+    #[test]
+    fn follower_controls_the_transport() {
+        let mut follower = MidiClockFollower::new();
+        let clocks: Vec<_> = (0..4).map(|i| (i * 1000, MidiMessage::Clock)).collect();
+        follow(&mut follower, &clocks);
+        assert!(!follower.is_running());
+        assert_eq!(
+            follow(&mut follower, &[(3500, MidiMessage::Start)]),
+            [TransportCommand::LocateAndStart(0)]
+        );
+        assert!(follower.is_running());
+        assert_eq!(follower.quarters_at(3800), 0.0);
+        follow(
+            &mut follower,
+            &[(4000, MidiMessage::Clock), (5000, MidiMessage::Clock)],
+        );
+        assert_eq!(follower.quarters_at(5500), 1.5 / 24.0);
+        assert_eq!(follower.quarters_at(9000), 2.0 / 24.0);
+        assert_eq!(
+            follow(&mut follower, &[(5600, MidiMessage::Stop)]),
+            [TransportCommand::Stop]
+        );
+        // 8 midi beats are 2 quarter notes, or one second at 120 bpm.
+        assert_eq!(
+            follow(&mut follower, &[(6000, MidiMessage::SongPosition(8))]),
+            [TransportCommand::Locate(48000)]
+        );
+        assert_eq!(follower.quarters_at(6000), 2.0);
+        assert_eq!(
+            follow(&mut follower, &[(7000, MidiMessage::Continue)]),
+            [TransportCommand::Start]
+        );
+    }
+
+    #[test]
+    fn timebase_publishes_the_follower_position() {
+        let mut follower = MidiClockFollower::new();
+        let clocks: Vec<_> = (0..2).map(|i| (i * 1000, MidiMessage::Clock)).collect();
+        follow(&mut follower, &clocks);
+        follow(&mut follower, &[(2000, MidiMessage::SongPosition(22))]);
+        follower.publish(2000);
+        let bbt = follower.timebase().bbt();
+        assert_eq!((bbt.bar, bbt.beat, bbt.tick), (2, 2, 960));
+        assert_eq!(bbt.bpm, 120.0);
+        assert!(bbt.valid());
+    }
+
+    #[test]
+    fn timebase_rejects_non_positive_values() {
+        let follower = MidiClockFollower::new();
+        assert_eq!(
+            follower.timebase().with_ticks_per_beat(0.0).unwrap_err(),
+            TransportBBTValidationError::TicksPerBeatRange
+        );
+        assert_eq!(
+            follower
+                .timebase()
+                .with_time_signature(0.0, 4.0)
+                .unwrap_err(),
+            TransportBBTValidationError::SigNumRange
+        );
+        assert_eq!(
+            follower
+                .timebase()
+                .with_time_signature(3.0, f32::NAN)
+                .unwrap_err(),
+            TransportBBTValidationError::SigDenomRange
+        );
+        let bbt = follower
+            .timebase()
+            .with_time_signature(3.0, 8.0)
+            .and_then(|timebase| timebase.with_ticks_per_beat(960.0))
+            .unwrap()
+            .bbt();
+        assert_eq!(
+            (bbt.sig_num, bbt.sig_denom, bbt.ticks_per_beat),
+            (3.0, 8.0, 960.0)
+        );
+    }
+}
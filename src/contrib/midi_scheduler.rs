@@ -0,0 +1,227 @@
+//! Sample accurate midi output of events that are scheduled from non real-time threads.
+//!
+//! [`channel`] creates a [`MidiScheduler`], which accepts midi events stamped with the frame time
+//! or the microsecond time at which they should be played, and a [`MidiSchedulerOutput`], which
+//! writes the events into a `MidiWriter` during the process cycle in which they are due. Events
+//! are passed through a lock-free ring buffer, so the output never blocks.
+//!
+//! ```no_run
+//! use jack::contrib::midi_scheduler::{self, Timestamp};
+//!
+//! let (client, _status) =
+//!     jack::Client::new("midi_scheduler", jack::ClientOptions::default()).unwrap();
+//! let mut port = client
+//!     .register_port("out", jack::MidiOut::default())
+//!     .unwrap();
+//! let (mut scheduler, mut output) = midi_scheduler::channel(4096).unwrap();
+//! let handler = jack::contrib::ClosureProcessHandler::new(move |_, ps| {
+//!     let mut writer = port.writer(ps);
+//!     output.write_due(ps, &mut writer).unwrap();
+//!     jack::Control::Continue
+//! });
+//! let active_client = client.activate_async((), handler).unwrap();
+//!
+//! // Play a note one second from now.
+//! let now = active_client.as_client().time();
+//! let note_on = jack::MidiMessage::NoteOn {
+//!     channel: 0,
+//!     note: 60,
+//!     velocity: 100,
+//! };
+//! scheduler
+//!     .schedule(Timestamp::Usecs(now + 1_000_000), &note_on)
+//!     .unwrap();
+//! ```
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::{
+    Error, Frames, MidiMessage, MidiWriter, ProcessScope, RingBuffer, RingBufferReader,
+    RingBufferWriter, Time,
+};
+
+/// The size of the header that precedes the bytes of each event in the ring buffer: the kind of
+/// timestamp, the timestamp and the number of bytes.
+const HEADER_SIZE: usize = 1 + 8 + 4;
+
+const FRAME_STAMP: u8 = 0;
+const USECS_STAMP: u8 = 1;
+
+/// The time at which a scheduled event should be played.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Timestamp {
+    /// An absolute frame time, as returned by `Client::frame_time`.
+    Frame(Frames),
+    /// An absolute time in microseconds, as returned by `Client::time`.
+    Usecs(Time),
+}
+
+impl Timestamp {
+    fn encode(self, len: u32) -> [u8; HEADER_SIZE] {
+        let (kind, stamp) = match self {
+            Timestamp::Frame(frame) => (FRAME_STAMP, u64::from(frame)),
+            Timestamp::Usecs(usecs) => (USECS_STAMP, usecs),
+        };
+        let mut header = [0; HEADER_SIZE];
+        header[0] = kind;
+        header[1..9].copy_from_slice(&stamp.to_le_bytes());
+        header[9..].copy_from_slice(&len.to_le_bytes());
+        header
+    }
+
+    fn decode(header: &[u8; HEADER_SIZE]) -> (Timestamp, usize) {
+        let stamp = u64::from_le_bytes(header[1..9].try_into().unwrap());
+        let len = u32::from_le_bytes(header[9..].try_into().unwrap()) as usize;
+        let timestamp = match header[0] {
+            FRAME_STAMP => Timestamp::Frame(stamp as Frames),
+            _ => Timestamp::Usecs(stamp),
+        };
+        (timestamp, len)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    late: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Create a scheduler and its output, connected by a ring buffer of `capacity` bytes.
+///
+/// Each event takes up its size plus 13 bytes of the capacity until it is written.
+pub fn channel(capacity: usize) -> Result<(MidiScheduler, MidiSchedulerOutput), Error> {
+    let mut ringbuffer = RingBuffer::new(capacity)?;
+    ringbuffer.mlock();
+    let (reader, writer) = ringbuffer.into_reader_writer();
+    let counters = Arc::new(Counters::default());
+    let scheduler = MidiScheduler {
+        writer,
+        counters: counters.clone(),
+    };
+    let output = MidiSchedulerOutput { reader, counters };
+    Ok((scheduler, output))
+}
+
+/// The non real-time half of a midi scheduler, used to schedule events.
+///
+/// Created with [`channel`].
+pub struct MidiScheduler {
+    writer: RingBufferWriter,
+    counters: Arc<Counters>,
+}
+
+impl MidiScheduler {
+    /// Schedule `message` to be played at `timestamp`.
+    ///
+    /// Events are written in the order they are scheduled, so they should be scheduled in order
+    /// of their timestamps. An event that is due before the event scheduled ahead of it is played
+    /// right after that event.
+    ///
+    /// `Err(Error::NotEnoughSpace)` is returned if the ring buffer is full.
+    pub fn schedule(&mut self, timestamp: Timestamp, message: &MidiMessage) -> Result<(), Error> {
+        let mut buffer = [0; MidiMessage::MAX_SHORT_MESSAGE_SIZE];
+        let bytes = message.encode(&mut buffer)?;
+        self.schedule_raw(timestamp, bytes)
+    }
+
+    /// Schedule raw midi bytes to be played at `timestamp`.
+    ///
+    /// The bytes must be a single normalised midi event, see `MidiWriter::write`.
+    /// `Err(Error::NotEnoughSpace)` is returned if the ring buffer is full and
+    /// `Err(Error::InvalidMidiMessage)` if `bytes` is empty.
+    pub fn schedule_raw(&mut self, timestamp: Timestamp, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.is_empty() {
+            return Err(Error::InvalidMidiMessage("empty message"));
+        }
+        let len: u32 = bytes.len().try_into().map_err(|_| Error::NotEnoughSpace)?;
+        if self.writer.space() < HEADER_SIZE + bytes.len() {
+            return Err(Error::NotEnoughSpace);
+        }
+        self.writer.write_buffer(&timestamp.encode(len));
+        self.writer.write_buffer(bytes);
+        Ok(())
+    }
+
+    /// The number of events that were due before the cycle in which they were read. Late events
+    /// are played at the start of the cycle.
+    pub fn late_events(&self) -> u64 {
+        self.counters.late.load(Ordering::Relaxed)
+    }
+
+    /// The number of events that were discarded because they did not fit into the port buffer.
+    pub fn dropped_events(&self) -> u64 {
+        self.counters.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// The real-time half of a midi scheduler, used in the process callback.
+///
+/// Created with [`channel`].
+pub struct MidiSchedulerOutput {
+    reader: RingBufferReader,
+    counters: Arc<Counters>,
+}
+
+impl MidiSchedulerOutput {
+    /// Write all events that are due in the current cycle into `writer` and return how many were
+    /// written.
+    ///
+    /// Events that are late are written at offset `0` and counted, see
+    /// [`MidiScheduler::late_events`]. This does not allocate or block.
+    ///
+    /// `Err(Error::TimeError)` or `Err(Error::WeakFunctionNotFound)` is returned if the cycle
+    /// times can not be determined, see `ProcessScope::cycle_times`.
+    pub fn write_due(
+        &mut self,
+        ps: &ProcessScope,
+        writer: &mut MidiWriter,
+    ) -> Result<usize, Error> {
+        let times = ps.cycle_times()?;
+        let n_frames = ps.n_frames();
+        let mut written = 0;
+        let mut min_offset = 0;
+        let mut header = [0; HEADER_SIZE];
+        loop {
+            if self.reader.peek(&mut header) < HEADER_SIZE {
+                break;
+            }
+            let (timestamp, len) = Timestamp::decode(&header);
+            if self.reader.space() < HEADER_SIZE + len {
+                // The scheduler has not finished writing the event.
+                break;
+            }
+            // Frames are counted with wrapping arithmetic.
+            let offset = match timestamp {
+                Timestamp::Frame(frame) => {
+                    i64::from(frame.wrapping_sub(times.current_frames) as i32)
+                }
+                Timestamp::Usecs(usecs) => {
+                    let cycle_usecs = times.next_usecs.saturating_sub(times.current_usecs).max(1);
+                    let elapsed = usecs as i64 - times.current_usecs as i64;
+                    elapsed * i64::from(n_frames) / cycle_usecs as i64
+                }
+            };
+            if offset >= i64::from(n_frames) {
+                break;
+            }
+            if offset < 0 {
+                self.counters.late.fetch_add(1, Ordering::Relaxed);
+            }
+            let offset = (offset.max(0) as Frames).max(min_offset);
+            self.reader.advance(HEADER_SIZE);
+            match writer.reserve(offset, len) {
+                Ok(bytes) => {
+                    self.reader.read_buffer(bytes);
+                    min_offset = offset;
+                    written += 1;
+                }
+                Err(_) => {
+                    self.reader.advance(len);
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        Ok(written)
+    }
+}
@@ -0,0 +1,747 @@
+//! MIDI Time Code synchronisation with the JACK transport.
+//!
+//! [`MtcGenerator`] sends quarter frame and full frame messages that follow the JACK transport, and
+//! [`MtcReader`] assembles them back into a [`Timecode`] and can make the transport chase it.
+//! Timecode is counted from transport frame `0`, which is `00:00:00:00`.
+//!
+//! ```no_run
+//! use jack::contrib::mtc::{MtcFrameRate, MtcGenerator};
+//!
+//! let (client, _status) = jack::Client::new("mtc", jack::ClientOptions::default()).unwrap();
+//! let mut port = client
+//!     .register_port("mtc_out", jack::MidiOut::default())
+//!     .unwrap();
+//! let mut generator = MtcGenerator::new(MtcFrameRate::Fps25);
+//! let handler = jack::contrib::ClosureProcessHandler::new(move |client, ps| {
+//!     let mut writer = port.writer(ps);
+//!     generator.process(client, ps, &mut writer).unwrap();
+//!     jack::Control::Continue
+//! });
+//! let _active_client = client.activate_async((), handler).unwrap();
+//! ```
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::contrib::transport_sync::{CycleWriter, NextCycle, TransportCommand};
+use crate::{
+    Client, Error, Frames, MidiIn, MidiMessage, MidiWriter, Port, ProcessScope, TransportState,
+};
+
+/// The number of quarter frame messages that make up a timecode. They span two video frames.
+const PIECES: u64 = 8;
+
+/// The number of video frames in ten minutes of 29.97 fps drop frame timecode.
+const DROP_FRAMES_PER_TEN_MINUTES: u64 = 10 * 60 * 30 - 9 * 2;
+
+/// The number of video frames in a minute of 29.97 fps drop frame timecode that is not a multiple
+/// of ten.
+const DROP_FRAMES_PER_MINUTE: u64 = 60 * 30 - 2;
+
+/// The frame rate of a timecode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MtcFrameRate {
+    /// 24 frames per second, as used for film.
+    Fps24,
+    /// 25 frames per second, as used for PAL video.
+    Fps25,
+    /// 29.97 frames per second drop frame, as used for NTSC video. Frames `0` and `1` are skipped
+    /// at the start of every minute that is not a multiple of ten.
+    Fps2997Drop,
+    /// 30 frames per second.
+    Fps30,
+}
+
+impl MtcFrameRate {
+    /// The number of video frames per second.
+    pub fn fps(self) -> f64 {
+        match self {
+            MtcFrameRate::Fps24 => 24.0,
+            MtcFrameRate::Fps25 => 25.0,
+            MtcFrameRate::Fps2997Drop => 30_000.0 / 1001.0,
+            MtcFrameRate::Fps30 => 30.0,
+        }
+    }
+
+    /// The number of audio frames per video frame at `sample_rate`, as published with
+    /// `TransportPosition::set_audio_frames_per_video_frame`.
+    pub fn audio_frames_per_video_frame(self, sample_rate: Frames) -> f32 {
+        (f64::from(sample_rate) / self.fps()) as f32
+    }
+
+    /// The number of frames in a second of the timecode.
+    fn nominal_fps(self) -> u64 {
+        match self {
+            MtcFrameRate::Fps24 => 24,
+            MtcFrameRate::Fps25 => 25,
+            MtcFrameRate::Fps2997Drop | MtcFrameRate::Fps30 => 30,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            MtcFrameRate::Fps24 => 0,
+            MtcFrameRate::Fps25 => 1,
+            MtcFrameRate::Fps2997Drop => 2,
+            MtcFrameRate::Fps30 => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> MtcFrameRate {
+        match code & 0x3 {
+            0 => MtcFrameRate::Fps24,
+            1 => MtcFrameRate::Fps25,
+            2 => MtcFrameRate::Fps2997Drop,
+            _ => MtcFrameRate::Fps30,
+        }
+    }
+}
+
+/// A SMPTE timecode of hours, minutes, seconds and frames.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub rate: MtcFrameRate,
+}
+
+impl Timecode {
+    /// The size of a full frame message.
+    pub const FULL_FRAME_SIZE: usize = 10;
+
+    /// The timecode of the video frame `count` frames after `00:00:00:00`. Timecodes wrap around
+    /// after 24 hours.
+    pub fn from_frame_count(count: u64, rate: MtcFrameRate) -> Timecode {
+        let fps = rate.nominal_fps();
+        let mut count = count % frames_per_day(rate);
+        if rate == MtcFrameRate::Fps2997Drop {
+            // Add back the frame numbers that were dropped.
+            let ten_minutes = count / DROP_FRAMES_PER_TEN_MINUTES;
+            let rest = count % DROP_FRAMES_PER_TEN_MINUTES;
+            count += 18 * ten_minutes;
+            if rest >= 2 {
+                count += 2 * ((rest - 2) / DROP_FRAMES_PER_MINUTE);
+            }
+        }
+        Timecode {
+            hours: (count / (3600 * fps)) as u8,
+            minutes: (count / (60 * fps) % 60) as u8,
+            seconds: (count / fps % 60) as u8,
+            frames: (count % fps) as u8,
+            rate,
+        }
+    }
+
+    /// The timecode of the video frame that contains the time `seconds` after `00:00:00:00`.
+    pub fn from_seconds(seconds: f64, rate: MtcFrameRate) -> Timecode {
+        // Allow for rounding errors at frame boundaries.
+        let count = (seconds.max(0.0) * rate.fps() + 1e-6).floor() as u64;
+        Timecode::from_frame_count(count, rate)
+    }
+
+    /// The number of video frames since `00:00:00:00`.
+    pub fn frame_count(&self) -> u64 {
+        let fps = self.rate.nominal_fps();
+        let minutes = 60 * u64::from(self.hours) + u64::from(self.minutes);
+        let count = (60 * minutes + u64::from(self.seconds)) * fps + u64::from(self.frames);
+        if self.rate == MtcFrameRate::Fps2997Drop {
+            count - 2 * (minutes - minutes / 10)
+        } else {
+            count
+        }
+    }
+
+    /// The time of the start of this timecode in seconds since `00:00:00:00`.
+    pub fn to_seconds(&self) -> f64 {
+        self.frame_count() as f64 / self.rate.fps()
+    }
+
+    /// Returns `true` if all fields are in range and the frame is not dropped.
+    pub fn is_valid(&self) -> bool {
+        let is_dropped = self.rate == MtcFrameRate::Fps2997Drop
+            && self.frames < 2
+            && self.seconds == 0
+            && !self.minutes.is_multiple_of(10);
+        self.hours < 24
+            && self.minutes < 60
+            && self.seconds < 60
+            && u64::from(self.frames) < self.rate.nominal_fps()
+            && !is_dropped
+    }
+
+    /// The quarter frame message that carries `piece` of this timecode, between `0` and `7`.
+    pub fn quarter_frame(&self, piece: u8) -> MidiMessage<'static> {
+        let piece = piece & 0x7;
+        let value = match piece {
+            0 => self.frames & 0xF,
+            1 => self.frames >> 4 & 0x1,
+            2 => self.seconds & 0xF,
+            3 => self.seconds >> 4 & 0x3,
+            4 => self.minutes & 0xF,
+            5 => self.minutes >> 4 & 0x3,
+            6 => self.hours & 0xF,
+            _ => self.rate.code() << 1 | self.hours >> 4 & 0x1,
+        };
+        MidiMessage::MtcQuarterFrame { piece, value }
+    }
+
+    /// Encode this timecode as a full frame message for all devices.
+    pub fn full_frame(&self) -> [u8; Timecode::FULL_FRAME_SIZE] {
+        [
+            0xF0,
+            0x7F,
+            0x7F,
+            0x01,
+            0x01,
+            self.rate.code() << 5 | self.hours & 0x1F,
+            self.minutes & 0x3F,
+            self.seconds & 0x3F,
+            self.frames & 0x1F,
+            0xF7,
+        ]
+    }
+
+    /// Decode a full frame message for any device. `None` is returned if `bytes` is not a full
+    /// frame message or the timecode is not valid.
+    pub fn from_full_frame(bytes: &[u8]) -> Option<Timecode> {
+        match *bytes {
+            [0xF0, 0x7F, _, 0x01, 0x01, hours, minutes, seconds, frames, 0xF7] => {
+                let timecode = Timecode {
+                    hours: hours & 0x1F,
+                    minutes,
+                    seconds,
+                    frames,
+                    rate: MtcFrameRate::from_code(hours >> 5),
+                };
+                Some(timecode).filter(Timecode::is_valid)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.rate {
+            MtcFrameRate::Fps2997Drop => ';',
+            _ => ':',
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+fn frames_per_day(rate: MtcFrameRate) -> u64 {
+    match rate {
+        MtcFrameRate::Fps2997Drop => 24 * 6 * DROP_FRAMES_PER_TEN_MINUTES,
+        _ => 24 * 3600 * rate.nominal_fps(),
+    }
+}
+
+/// Generates MIDI Time Code from the JACK transport.
+///
+/// The timecode is derived from the transport frame and the frame rate of the transport position.
+/// While the transport is rolling, quarter frames are sent in sequences of eight that start on
+/// even video frames. A full frame message is sent whenever the transport is located, so devices
+/// can follow the position while the transport is stopped.
+#[derive(Clone, Debug)]
+pub struct MtcGenerator {
+    rate: MtcFrameRate,
+    /// The next quarter frame to send, counted from `00:00:00:00`. `None` while stopped.
+    next_quarter: Option<u64>,
+    next_cycle: NextCycle,
+    /// The transport frame for which a full frame message was sent.
+    located_frame: Option<Frames>,
+}
+
+impl MtcGenerator {
+    /// Create a generator for timecode at `rate` that is stopped.
+    pub fn new(rate: MtcFrameRate) -> MtcGenerator {
+        MtcGenerator {
+            rate,
+            next_quarter: None,
+            next_cycle: NextCycle::default(),
+            located_frame: None,
+        }
+    }
+
+    /// The frame rate of the generated timecode.
+    pub fn rate(&self) -> MtcFrameRate {
+        self.rate
+    }
+
+    /// Returns `true` if the generator is sending quarter frames.
+    pub fn is_running(&self) -> bool {
+        self.next_quarter.is_some()
+    }
+
+    /// Write the messages for the current cycle into `writer` and return how many were written.
+    pub fn process(
+        &mut self,
+        client: &Client,
+        ps: &ProcessScope,
+        writer: &mut MidiWriter,
+    ) -> Result<usize, Error> {
+        let transport = client.transport().query()?;
+        let sample_rate = transport
+            .pos
+            .frame_rate()
+            .unwrap_or_else(|| client.sample_rate());
+        let mut writer = CycleWriter::new(writer);
+        self.cycle(
+            transport.state,
+            transport.pos.frame(),
+            ps.n_frames(),
+            sample_rate,
+            |time, message| writer.write(time, message),
+        );
+        writer.finish()
+    }
+
+    /// Emit the messages of a cycle that starts at transport `frame`.
+    fn cycle<F>(
+        &mut self,
+        state: TransportState,
+        frame: Frames,
+        n_frames: Frames,
+        sample_rate: Frames,
+        mut emit: F,
+    ) where
+        F: FnMut(Frames, &MidiMessage),
+    {
+        let sample_rate = f64::from(sample_rate);
+        let rate = self.rate;
+        let send_full_frame = |emit: &mut F| {
+            let timecode = Timecode::from_seconds(f64::from(frame) / sample_rate, rate);
+            emit(0, &MidiMessage::SysEx(&timecode.full_frame()));
+        };
+        match state {
+            TransportState::Stopped | TransportState::Starting => {
+                self.next_quarter = None;
+                self.next_cycle.reset();
+                if self.located_frame != Some(frame) {
+                    send_full_frame(&mut emit);
+                    self.located_frame = Some(frame);
+                }
+            }
+            TransportState::Rolling => {
+                let quarters_per_frame = 4.0 * self.rate.fps() / sample_rate;
+                let start = f64::from(frame) * quarters_per_frame;
+                if self.next_cycle.advance(frame, n_frames) {
+                    self.next_quarter = None;
+                    self.located_frame = None;
+                }
+                let mut quarter = match self.next_quarter {
+                    Some(quarter) => quarter,
+                    None => {
+                        if self.located_frame != Some(frame) {
+                            send_full_frame(&mut emit);
+                        }
+                        (start / PIECES as f64).ceil() as u64 * PIECES
+                    }
+                };
+                self.located_frame = None;
+                let end = start + f64::from(n_frames) * quarters_per_frame;
+                while (quarter as f64) < end {
+                    let offset =
+                        ((quarter as f64 - start).max(0.0) / quarters_per_frame).round() as Frames;
+                    let piece = quarter % PIECES;
+                    let timecode = Timecode::from_frame_count((quarter - piece) / 4, self.rate);
+                    emit(
+                        offset.min(n_frames - 1),
+                        &timecode.quarter_frame(piece as u8),
+                    );
+                    quarter += 1;
+                }
+                self.next_quarter = Some(quarter);
+            }
+        }
+    }
+}
+
+/// A timecode received in the current cycle.
+#[derive(Copy, Clone, Debug)]
+struct Received {
+    timecode: Timecode,
+    /// The offset of the message in the cycle.
+    time: Frames,
+    is_quarter_frame: bool,
+}
+
+/// Reads MIDI Time Code and makes the JACK transport chase it.
+///
+/// Quarter frames are only assembled into a timecode when all eight are received in order. As
+/// they span two video frames, the timecode of a complete sequence is the one of the video frame
+/// in which its first quarter frame was sent.
+#[derive(Clone, Debug)]
+pub struct MtcReader {
+    /// The values of the quarter frames of the current sequence.
+    pieces: [u8; PIECES as usize],
+    /// The next quarter frame of the current sequence. `None` while waiting for the first.
+    next_piece: Option<u8>,
+    timecode: Option<Timecode>,
+    /// The difference in video frames above which the transport is relocated.
+    tolerance: f64,
+    /// The frame time at which the last quarter frame was received.
+    last_quarter_frame: Option<Frames>,
+    is_running: bool,
+}
+
+impl Default for MtcReader {
+    fn default() -> Self {
+        MtcReader {
+            pieces: [0; PIECES as usize],
+            next_piece: None,
+            timecode: None,
+            tolerance: 2.0,
+            last_quarter_frame: None,
+            is_running: false,
+        }
+    }
+}
+
+impl MtcReader {
+    /// Create a reader that has not received any timecode.
+    pub fn new() -> MtcReader {
+        MtcReader::default()
+    }
+
+    /// Set the difference between the transport and the received timecode, in video frames,
+    /// above which [`MtcReader::chase`] relocates the transport. Defaults to `2.0`.
+    pub fn with_tolerance(mut self, video_frames: f64) -> Self {
+        self.tolerance = video_frames;
+        self
+    }
+
+    /// The last complete timecode that was received.
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.timecode
+    }
+
+    /// Returns `true` while quarter frames are received.
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Handle `message` and return the timecode it completes, if any.
+    ///
+    /// Messages other than quarter frames and full frames are ignored.
+    pub fn read(&mut self, message: &MidiMessage) -> Option<Timecode> {
+        match *message {
+            MidiMessage::MtcQuarterFrame { piece, value } => {
+                if piece == 0 {
+                    self.next_piece = Some(0);
+                }
+                if self.next_piece != Some(piece) {
+                    self.next_piece = None;
+                    return None;
+                }
+                self.pieces[usize::from(piece)] = value;
+                if u64::from(piece) + 1 < PIECES {
+                    self.next_piece = Some(piece + 1);
+                    return None;
+                }
+                self.next_piece = None;
+                let p = &self.pieces;
+                self.accept(Timecode {
+                    hours: (p[7] & 0x1) << 4 | p[6],
+                    minutes: p[5] << 4 | p[4],
+                    seconds: p[3] << 4 | p[2],
+                    frames: p[1] << 4 | p[0],
+                    rate: MtcFrameRate::from_code(p[7] >> 1),
+                })
+            }
+            MidiMessage::SysEx(bytes) => {
+                let timecode = Timecode::from_full_frame(bytes)?;
+                self.next_piece = None;
+                self.accept(timecode)
+            }
+            _ => None,
+        }
+    }
+
+    fn accept(&mut self, timecode: Timecode) -> Option<Timecode> {
+        if timecode.is_valid() {
+            self.timecode = Some(timecode);
+            Some(timecode)
+        } else {
+            None
+        }
+    }
+
+    /// Read the messages that `port` received in the current cycle and make the transport chase
+    /// the timecode. Returns the last timecode that was completed in this cycle.
+    ///
+    /// The transport is started when quarter frames arrive and stopped when they stop arriving
+    /// for two video frames. It is relocated when it is further than the tolerance from the
+    /// received timecode, see [`MtcReader::with_tolerance`].
+    pub fn chase(
+        &mut self,
+        client: &Client,
+        ps: &ProcessScope,
+        port: &Port<MidiIn>,
+    ) -> Result<Option<Timecode>, Error> {
+        let transport = client.transport();
+        let status = transport.query()?;
+        let sample_rate = status
+            .pos
+            .frame_rate()
+            .unwrap_or_else(|| client.sample_rate());
+        let cycle_start = ps.last_frame_time();
+        let mut received = None;
+        for event in port.iter(ps) {
+            let message = match MidiMessage::try_from(event) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            let is_quarter_frame = matches!(message, MidiMessage::MtcQuarterFrame { .. });
+            if is_quarter_frame {
+                self.last_quarter_frame = Some(cycle_start.wrapping_add(event.time));
+            }
+            if let Some(timecode) = self.read(&message) {
+                received = Some(Received {
+                    timecode,
+                    time: event.time,
+                    is_quarter_frame,
+                });
+            }
+        }
+        let command = self.follow(
+            status.state,
+            status.pos.frame(),
+            cycle_start.wrapping_add(ps.n_frames()),
+            ps.n_frames(),
+            sample_rate,
+            received,
+        );
+        if let Some(command) = command {
+            command.apply(&transport)?;
+        }
+        Ok(received.map(|received| received.timecode))
+    }
+
+    /// Decide how the transport should follow the timecode received in a cycle that starts at
+    /// transport `frame` and ends at frame time `cycle_end`.
+    fn follow(
+        &mut self,
+        state: TransportState,
+        frame: Frames,
+        cycle_end: Frames,
+        n_frames: Frames,
+        sample_rate: Frames,
+        received: Option<Received>,
+    ) -> Option<TransportCommand> {
+        let sample_rate = f64::from(sample_rate);
+        let was_running = self.is_running;
+        if received.is_some_and(|received| received.is_quarter_frame) {
+            self.is_running = true;
+        }
+        if let Some(last_quarter_frame) = self.last_quarter_frame {
+            let frames_per_video_frame = self.timecode.map_or(sample_rate / 24.0, |timecode| {
+                sample_rate / timecode.rate.fps()
+            });
+            let silence = f64::from(cycle_end.wrapping_sub(last_quarter_frame));
+            if silence > 2.0 * frames_per_video_frame {
+                self.is_running = false;
+                self.last_quarter_frame = None;
+            }
+        }
+
+        let locate = received.and_then(|received| {
+            let fps = received.timecode.rate.fps();
+            let mut seconds = received.timecode.to_seconds();
+            if received.is_quarter_frame {
+                // The last quarter frame of a sequence is sent 1.75 video frames after its first.
+                seconds += 1.75 / fps;
+            }
+            let target = seconds * sample_rate;
+            let rolls = state == TransportState::Rolling;
+            let current = f64::from(frame) + if rolls { f64::from(received.time) } else { 0.0 };
+            if (target - current).abs() <= self.tolerance * sample_rate / fps {
+                return None;
+            }
+            // Locate to the position at the start of the next cycle.
+            let ahead = if self.is_running {
+                f64::from(n_frames - received.time)
+            } else {
+                0.0
+            };
+            Some((target + ahead).round() as Frames)
+        });
+
+        let start = self.is_running && !was_running && state == TransportState::Stopped;
+        let stop = !self.is_running && was_running && state != TransportState::Stopped;
+        match (locate, start, stop) {
+            (Some(frame), true, _) => Some(TransportCommand::LocateAndStart(frame)),
+            (Some(frame), false, _) => Some(TransportCommand::Locate(frame)),
+            (None, true, _) => Some(TransportCommand::Start),
+            (None, false, true) => Some(TransportCommand::Stop),
+            (None, false, false) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_frame_timecode_skips_frame_numbers() {
+        let timecode = |count| Timecode::from_frame_count(count, MtcFrameRate::Fps2997Drop);
+        assert_eq!(timecode(1799).to_string(), "00:00:59;29");
+        assert_eq!(timecode(1800).to_string(), "00:01:00;02");
+        assert_eq!(timecode(17981).to_string(), "00:09:59;29");
+        assert_eq!(timecode(17982).to_string(), "00:10:00;00");
+        for count in [0, 1799, 1800, 17982, 107_892, 2_589_407].iter() {
+            assert!(timecode(*count).is_valid());
+            assert_eq!(timecode(*count).frame_count(), *count);
+        }
+        // Drop frame timecode keeps up with the time of day.
+        assert_eq!(timecode(2_589_408).to_string(), "00:00:00;00");
+        assert_eq!(
+            Timecode::from_seconds(3600.0, MtcFrameRate::Fps2997Drop).to_string(),
+            "01:00:00;00"
+        );
+    }
+
+    #[test]
+    fn full_frames_roundtrip() {
+        let timecode = Timecode {
+            hours: 23,
+            minutes: 59,
+            seconds: 58,
+            frames: 24,
+            rate: MtcFrameRate::Fps25,
+        };
+        let bytes = timecode.full_frame();
+        assert_eq!(
+            bytes,
+            [0xF0, 0x7F, 0x7F, 0x01, 0x01, 0x37, 59, 58, 24, 0xF7]
+        );
+        assert_eq!(Timecode::from_full_frame(&bytes), Some(timecode));
+        let mut reader = MtcReader::new();
+        assert_eq!(reader.read(&MidiMessage::SysEx(&bytes)), Some(timecode));
+
+        // Frame 25 does not exist at 25 fps.
+        let invalid = [0xF0, 0x7F, 0x7F, 0x01, 0x01, 0x37, 59, 58, 25, 0xF7];
+        assert_eq!(Timecode::from_full_frame(&invalid), None);
+    }
+
+    fn generate(
+        generator: &mut MtcGenerator,
+        state: TransportState,
+        frame: Frames,
+    ) -> Vec<(Frames, Vec<u8>)> {
+        let mut messages = Vec::new();
+        generator.cycle(state, frame, 1000, 48000, |time, message| {
+            let mut buffer = [0; MidiMessage::MAX_SHORT_MESSAGE_SIZE];
+            messages.push((time, message.encode(&mut buffer).unwrap().to_vec()));
+        });
+        messages
+    }
+
+    #[test]
+    fn generated_quarter_frames_are_read_back() {
+        let mut generator = MtcGenerator::new(MtcFrameRate::Fps24);
+        let mut reader = MtcReader::new();
+        let located = generate(&mut generator, TransportState::Stopped, 48000);
+        assert_eq!(
+            located,
+            [(0, vec![0xF0, 0x7F, 0x7F, 0x01, 0x01, 0, 0, 1, 0, 0xF7])]
+        );
+        assert_eq!(generate(&mut generator, TransportState::Stopped, 48000), []);
+
+        // At 24 fps and 48 kHz, a quarter frame is sent every 500 frames.
+        let mut timecodes = Vec::new();
+        for cycle in 0..16 {
+            let messages = generate(
+                &mut generator,
+                TransportState::Rolling,
+                48000 + cycle * 1000,
+            );
+            assert_eq!(messages.len(), 2);
+            assert_eq!((messages[0].0, messages[1].0), (0, 500));
+            for (_, bytes) in messages {
+                let message = MidiMessage::parse(&bytes).unwrap();
+                timecodes.extend(reader.read(&message).map(|t| t.to_string()));
+            }
+        }
+        assert!(generator.is_running());
+        assert_eq!(
+            timecodes,
+            ["00:00:01:00", "00:00:01:02", "00:00:01:04", "00:00:01:06"]
+        );
+
+        // Relocating sends a full frame and waits for the next even video frame.
+        let messages = generate(&mut generator, TransportState::Rolling, 96100);
+        assert_eq!(
+            messages[0],
+            (
+                0,
+                Timecode::from_seconds(2.0, MtcFrameRate::Fps24)
+                    .full_frame()
+                    .to_vec()
+            )
+        );
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn out_of_order_quarter_frames_are_ignored() {
+        let timecode = Timecode::from_frame_count(1234, MtcFrameRate::Fps30);
+        let mut reader = MtcReader::new();
+        for piece in (0..8).filter(|piece| *piece != 3) {
+            assert_eq!(reader.read(&timecode.quarter_frame(piece)), None);
+        }
+        let completed: Vec<_> = (0..8)
+            .filter_map(|piece| reader.read(&timecode.quarter_frame(piece)))
+            .collect();
+        assert_eq!(completed, [timecode]);
+    }
+
+    #[test]
+    fn reader_chases_the_timecode() {
+        let mut reader = MtcReader::new();
+        reader.last_quarter_frame = Some(200);
+        let received = |seconds: f64, time| Received {
+            timecode: Timecode::from_seconds(seconds, MtcFrameRate::Fps25),
+            time,
+            is_quarter_frame: true,
+        };
+        // A sequence that started at 10 seconds ends 1.75 video frames, or 3360 frames, later.
+        let command = reader.follow(
+            TransportState::Stopped,
+            0,
+            1000,
+            1000,
+            48000,
+            Some(received(10.0, 200)),
+        );
+        assert_eq!(
+            command,
+            Some(TransportCommand::LocateAndStart(480_000 + 3360 + 800))
+        );
+        assert!(reader.is_running());
+
+        // Close enough to the timecode.
+        reader.last_quarter_frame = Some(19_000);
+        let command = reader.follow(
+            TransportState::Rolling,
+            500_000,
+            20_000,
+            1000,
+            48000,
+            Some(received(10.36, 0)),
+        );
+        assert_eq!(command, None);
+
+        // The quarter frames stopped.
+        let command = reader.follow(TransportState::Rolling, 510_000, 30_000, 1000, 48000, None);
+        assert_eq!(command, Some(TransportCommand::Stop));
+        assert!(!reader.is_running());
+    }
+}
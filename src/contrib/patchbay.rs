@@ -0,0 +1,619 @@
+//! Saving and restoring the connections between ports.
+//!
+//! A [`Patchbay`] is a set of connections that can be captured from a running server, written to
+//! a human-editable JSON file and restored later:
+//!
+//! ```json
+//! {
+//!   "connections": [
+//!     {"output": "system:capture_1", "input": "recorder:in_1"},
+//!     {"output": "synth:out", "input": "system:playback_1"}
+//!   ]
+//! }
+//! ```
+//!
+//! ```no_run
+//! use jack::contrib::patchbay::Patchbay;
+//!
+//! let (client, _status) =
+//!     jack::Client::new("patchbay", jack::ClientOptions::default()).unwrap();
+//! let patchbay = Patchbay::capture(&client, None);
+//! patchbay
+//!     .write(std::fs::File::create("studio.json").unwrap())
+//!     .unwrap();
+//!
+//! let file = std::fs::File::open("studio.json").unwrap();
+//! let report = Patchbay::read(file).unwrap().restore(&client);
+//! for port in report.missing_ports {
+//!     eprintln!("missing port {}", port);
+//! }
+//! ```
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{self, Read, Write};
+use std::{error, fmt};
+
+use crate::{Client, Connection, Error, Graph, PortFlags};
+
+/// An error reading a patchbay file.
+#[derive(Debug)]
+pub enum PatchbayError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The file is not valid.
+    Invalid { line: usize, reason: &'static str },
+}
+
+impl fmt::Display for PatchbayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchbayError::Io(err) => write!(f, "{err}"),
+            PatchbayError::Invalid { line, reason } => {
+                write!(f, "invalid patchbay file at line {line}: {reason}")
+            }
+        }
+    }
+}
+
+impl error::Error for PatchbayError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PatchbayError::Io(err) => Some(err),
+            PatchbayError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatchbayError {
+    fn from(err: io::Error) -> PatchbayError {
+        PatchbayError::Io(err)
+    }
+}
+
+/// A set of connections between ports, identified by their full names.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Patchbay {
+    pub connections: BTreeSet<Connection>,
+}
+
+/// The differences between two [`Patchbay`]s, as returned by [`Patchbay::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PatchbayDiff {
+    pub added: Vec<Connection>,
+    pub removed: Vec<Connection>,
+}
+
+/// The outcome of [`Patchbay::restore`].
+#[derive(Debug, Default, PartialEq)]
+pub struct RestoreReport {
+    /// The connections that were made.
+    pub connected: Vec<Connection>,
+    /// The connections that already existed.
+    pub already_connected: Vec<Connection>,
+    /// The ports that do not exist, so their connections were skipped.
+    pub missing_ports: Vec<String>,
+    /// The connections that JACK refused.
+    pub failed: Vec<(Connection, Error)>,
+}
+
+impl RestoreReport {
+    /// Returns `true` if all connections exist now.
+    pub fn is_complete(&self) -> bool {
+        self.missing_ports.is_empty() && self.failed.is_empty()
+    }
+}
+
+impl Patchbay {
+    /// Capture the current connections of the server.
+    ///
+    /// If `client_pattern` is given, only connections from or to a client whose whole name
+    /// matches it are captured. It is a regular expression like the patterns of `Client::ports`.
+    pub fn capture(client: &Client, client_pattern: Option<&str>) -> Patchbay {
+        let graph = client.graph();
+        let clients: Option<HashSet<String>> = client_pattern.map(|pattern| {
+            let pattern = format!("^({pattern}):");
+            client
+                .ports(Some(&pattern), None, PortFlags::empty())
+                .iter()
+                .filter_map(|name| graph.ports.get(name))
+                .map(|port| port.client.clone())
+                .collect()
+        });
+        let mut patchbay = Patchbay::from_graph(&graph);
+        if let Some(clients) = clients {
+            patchbay.connections.retain(|connection| {
+                [&connection.output, &connection.input].iter().any(|name| {
+                    graph
+                        .ports
+                        .get(*name)
+                        .is_some_and(|port| clients.contains(&port.client))
+                })
+            });
+        }
+        patchbay
+    }
+
+    /// The connections of `graph`.
+    pub fn from_graph(graph: &Graph) -> Patchbay {
+        Patchbay {
+            connections: graph.connections.clone(),
+        }
+    }
+
+    /// Compute which connections were added and removed from `old` to `new`.
+    pub fn diff(old: &Patchbay, new: &Patchbay) -> PatchbayDiff {
+        PatchbayDiff {
+            added: new
+                .connections
+                .difference(&old.connections)
+                .cloned()
+                .collect(),
+            removed: old
+                .connections
+                .difference(&new.connections)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Make all connections of the patchbay. Existing connections that are not part of the
+    /// patchbay are kept.
+    ///
+    /// Connections that already exist count as restored. Connections from or to ports that do
+    /// not exist are skipped, and the missing ports are reported.
+    pub fn restore(&self, client: &Client) -> RestoreReport {
+        let mut report = RestoreReport::default();
+        let mut missing = BTreeSet::new();
+        for connection in &self.connections {
+            let mut is_missing = false;
+            for name in [&connection.output, &connection.input] {
+                // A name with a NUL byte can not be passed to JACK, so there is no such port.
+                if name.contains('\0') || client.port_by_name(name).is_none() {
+                    missing.insert(name.clone());
+                    is_missing = true;
+                }
+            }
+            if is_missing {
+                continue;
+            }
+            match client.connect_ports_by_name(&connection.output, &connection.input) {
+                Ok(()) => report.connected.push(connection.clone()),
+                Err(Error::PortAlreadyConnected(_, _)) => {
+                    report.already_connected.push(connection.clone())
+                }
+                Err(err) => report.failed.push((connection.clone(), err)),
+            }
+        }
+        report.missing_ports = missing.into_iter().collect();
+        report
+    }
+
+    /// Read a patchbay file.
+    pub fn read<R: Read>(mut reader: R) -> Result<Patchbay, PatchbayError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Patchbay::parse(&text)
+    }
+
+    /// Parse the JSON of a patchbay file. Unknown keys are ignored.
+    pub fn parse(text: &str) -> Result<Patchbay, PatchbayError> {
+        let mut parser = Parser {
+            text,
+            pos: 0,
+            depth: 0,
+        };
+        let document = parser.document()?;
+        let invalid = |reason| PatchbayError::Invalid { line: 1, reason };
+        let mut document = document
+            .into_object()
+            .ok_or_else(|| invalid("expected an object"))?;
+        let connections = match document.remove("connections") {
+            Some(connections) => connections
+                .into_array()
+                .ok_or_else(|| invalid("connections must be an array"))?,
+            None => Vec::new(),
+        };
+        let mut patchbay = Patchbay::default();
+        for (line, connection) in connections {
+            let invalid = |reason| PatchbayError::Invalid { line, reason };
+            let mut connection = connection
+                .into_object()
+                .ok_or_else(|| invalid("a connection must be an object"))?;
+            let mut end = |key| match connection.remove(key) {
+                Some(Value::String(name)) if name.contains('\0') => {
+                    Err(invalid("port names can not contain NUL"))
+                }
+                Some(Value::String(name)) => Ok(name),
+                _ => Err(invalid(
+                    "a connection needs an output and an input port name",
+                )),
+            };
+            let output = end("output")?;
+            let input = end("input")?;
+            patchbay.connections.insert(Connection { output, input });
+        }
+        Ok(patchbay)
+    }
+
+    /// Write the patchbay as JSON, one connection per line.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.to_json().as_bytes())
+    }
+
+    /// The JSON of a patchbay file, one connection per line.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"connections\": [");
+        for (i, connection) in self.connections.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            json.push_str("    {\"output\": ");
+            push_json_string(&mut json, &connection.output);
+            json.push_str(", \"input\": ");
+            push_json_string(&mut json, &connection.input);
+            json.push('}');
+        }
+        if !self.connections.is_empty() {
+            json.push_str("\n  ");
+        }
+        json.push_str("]\n}\n");
+        json
+    }
+}
+
+fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// A JSON value. Only strings, arrays and objects are kept, everything else is parsed and then
+/// discarded.
+#[derive(Debug)]
+enum Value {
+    String(String),
+    /// The elements with the line they start on.
+    Array(Vec<(usize, Value)>),
+    Object(BTreeMap<String, Value>),
+    Other,
+}
+
+impl Value {
+    fn into_object(self) -> Option<BTreeMap<String, Value>> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    fn into_array(self) -> Option<Vec<(usize, Value)>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+}
+
+/// The deepest nesting of arrays and objects that is parsed, so that the recursion of the parser
+/// can not overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// The number of arrays and objects that contain the current value.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<Value, PatchbayError> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.pos < self.text.len() {
+            return Err(self.invalid("unexpected text after the document"));
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, PatchbayError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.invalid("arrays and objects are nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(Value::String),
+            Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+                let rest = &self.text[self.pos..];
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))
+                    .unwrap_or(rest.len());
+                let token = &rest[..len];
+                let is_number = token.parse::<f64>().is_ok() && !token.starts_with('+');
+                if !is_number && !["true", "false", "null"].contains(&token) {
+                    return Err(self.invalid("unexpected value"));
+                }
+                self.pos += len;
+                Ok(Value::Other)
+            }
+            _ => Err(self.invalid("expected a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, PatchbayError> {
+        self.expect('{')?;
+        let mut object = BTreeMap::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Value::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            object.insert(key, value);
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Value::Object(object));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, PatchbayError> {
+        self.expect('[')?;
+        let mut array = Vec::new();
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Value::Array(array));
+        }
+        loop {
+            self.skip_whitespace();
+            let line = self.line();
+            array.push((line, self.value()?));
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(Value::Array(array));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, PatchbayError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let c = self
+                .next()
+                .ok_or_else(|| self.invalid("unterminated string"))?;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.invalid("invalid escape sequence")),
+                    };
+                    s.push(escaped);
+                }
+                c if (c as u32) < 0x20 => return Err(self.invalid("control character in string")),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, PatchbayError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            // A surrogate pair.
+            if !(self.eat('\\') && self.eat('u')) {
+                return Err(self.invalid("invalid escape sequence"));
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.invalid("invalid escape sequence"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.invalid("invalid escape sequence"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, PatchbayError> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.invalid("invalid escape sequence"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PatchbayError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.invalid(match expected {
+                '{' => "expected an object",
+                '[' => "expected an array",
+                '"' => "expected a string",
+                ':' => "expected ':'",
+                _ => "expected ',' or the end of the object or array",
+            }))
+        }
+    }
+
+    fn line(&self) -> usize {
+        self.text[..self.pos].matches('\n').count() + 1
+    }
+
+    fn invalid(&self, reason: &'static str) -> PatchbayError {
+        PatchbayError::Invalid {
+            line: self.line(),
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patchbays_roundtrip_through_json() {
+        let mut patchbay = Patchbay::default();
+        patchbay
+            .connections
+            .insert(Connection::new("system:capture_1", "recorder:in \"1\""));
+        patchbay
+            .connections
+            .insert(Connection::new("synth:out", "system:playback_1"));
+        let json = patchbay.to_json();
+        assert_eq!(
+            json,
+            concat!(
+                "{\n",
+                "  \"connections\": [\n",
+                "    {\"output\": \"synth:out\", \"input\": \"system:playback_1\"},\n",
+                "    {\"output\": \"system:capture_1\", \"input\": \"recorder:in \\\"1\\\"\"}\n",
+                "  ]\n",
+                "}\n"
+            )
+        );
+        assert_eq!(Patchbay::parse(&json).unwrap(), patchbay);
+        assert_eq!(
+            Patchbay::parse(&Patchbay::default().to_json()).unwrap(),
+            Patchbay::default()
+        );
+    }
+
+    #[test]
+    fn any_port_name_roundtrips() {
+        let names = [
+            " a: -> b\\c ",
+            "line\nbreak\r:\tout",
+            "# not a comment:\u{1}\u{7f}",
+            "ünï:😀/\u{2028}",
+        ];
+        let patchbay = Patchbay {
+            connections: names
+                .iter()
+                .flat_map(|output| {
+                    names
+                        .iter()
+                        .map(move |input| Connection::new(output, input))
+                })
+                .collect(),
+        };
+        assert_eq!(Patchbay::parse(&patchbay.to_json()).unwrap(), patchbay);
+    }
+
+    #[test]
+    fn hand_edited_files_are_parsed() {
+        let json = r#"{
+            "version": 1,
+            "comment": null,
+            "connections": [
+                {"input": "b:iné", "output": "a:out", "enabled": true}
+            ]
+        }"#;
+        let patchbay = Patchbay::parse(json).unwrap();
+        let connections: Vec<_> = patchbay.connections.into_iter().collect();
+        assert_eq!(connections, [Connection::new("a:out", "b:iné")]);
+    }
+
+    #[test]
+    fn invalid_files_report_the_line() {
+        let missing_input = "{\"connections\": [\n{\"output\": \"a:out\"}\n]}";
+        assert!(matches!(
+            Patchbay::parse(missing_input),
+            Err(PatchbayError::Invalid { line: 2, .. })
+        ));
+        let trailing_comma = "{\"connections\": [\n\n{\"output\": \"a\", \"input\": \"b\"},]}";
+        assert!(matches!(
+            Patchbay::parse(trailing_comma),
+            Err(PatchbayError::Invalid { line: 3, .. })
+        ));
+        let nul = "{\"connections\": [{\"output\": \"a\\u0000\", \"input\": \"b\"}]}";
+        assert!(matches!(
+            Patchbay::parse(nul),
+            Err(PatchbayError::Invalid { line: 1, .. })
+        ));
+        let nested = "[".repeat(100_000);
+        assert!(matches!(
+            Patchbay::parse(&nested),
+            Err(PatchbayError::Invalid { line: 1, .. })
+        ));
+        assert!(Patchbay::parse("[]").is_err());
+        assert!(Patchbay::parse("{} {}").is_err());
+    }
+
+    #[test]
+    fn diff_lists_added_and_removed_connections() {
+        let patchbay = |connections: &[(&str, &str)]| Patchbay {
+            connections: connections
+                .iter()
+                .map(|(output, input)| Connection::new(output, input))
+                .collect(),
+        };
+        let old = patchbay(&[("a:out", "b:in"), ("a:out", "c:in")]);
+        let new = patchbay(&[("a:out", "c:in"), ("d:out", "c:in")]);
+        assert_eq!(
+            Patchbay::diff(&old, &new),
+            PatchbayDiff {
+                added: vec![Connection::new("d:out", "c:in")],
+                removed: vec![Connection::new("a:out", "b:in")],
+            }
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Recording and playback of Standard MIDI Files.
+//!
+//! [`Smf`] reads and writes Standard MIDI Files of format 0 and 1. The [`record`] module captures
+//! a midi input port into an `Smf` and the [`play`] module plays an `Smf` on a midi output port,
+//! following the JACK transport.
+//!
+//! ```no_run
+//! let file = std::fs::File::open("song.mid").unwrap();
+//! let smf = jack::contrib::smf::Smf::read(std::io::BufReader::new(file)).unwrap();
+//! for track in &smf.tracks {
+//!     println!("{} events", track.len());
+//! }
+//! ```
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::{error, fmt};
+
+pub mod play;
+pub mod record;
+
+/// The tempo of a file without tempo events, 120 beats per minute, in microseconds per quarter
+/// note.
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+const END_OF_TRACK: u8 = 0x2F;
+const SET_TEMPO: u8 = 0x51;
+
+/// An error reading or writing a Standard MIDI File.
+#[derive(Debug)]
+pub enum SmfError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The file is malformed or uses a feature that is not supported.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SmfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SmfError::Io(err) => write!(f, "{err}"),
+            SmfError::Invalid(reason) => write!(f, "invalid standard midi file: {reason}"),
+        }
+    }
+}
+
+impl error::Error for SmfError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SmfError::Io(err) => Some(err),
+            SmfError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SmfError {
+    fn from(err: io::Error) -> SmfError {
+        SmfError::Io(err)
+    }
+}
+
+/// The layout of the tracks of a Standard MIDI File.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SmfFormat {
+    /// Format 0, a single track that holds all events.
+    SingleTrack,
+    /// Format 1, tracks that are played simultaneously. By convention, the first track holds the
+    /// tempo map.
+    MultiTrack,
+}
+
+/// The content of an event in a track.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SmfEventData {
+    /// A midi message. System exclusive messages include the leading `0xF0` and trailing `0xF7`
+    /// bytes.
+    Midi(Vec<u8>),
+    /// A tempo change in microseconds per quarter note.
+    Tempo(u32),
+    /// Any other meta event. The end of track meta event is implied and never stored.
+    Meta { kind: u8, data: Vec<u8> },
+}
+
+/// An event in a track.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmfEvent {
+    /// The time of the event in ticks since the start of the track.
+    pub tick: u64,
+    /// The content of the event.
+    pub data: SmfEventData,
+}
+
+/// A Standard MIDI File.
+///
+/// Only metrical time divisions, in ticks per quarter note, are supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Smf {
+    /// The layout of the tracks.
+    pub format: SmfFormat,
+    /// The number of ticks in a quarter note.
+    pub ticks_per_quarter: u16,
+    /// The tracks, each with events sorted by tick.
+    pub tracks: Vec<Vec<SmfEvent>>,
+}
+
+impl Smf {
+    /// Read a Standard MIDI File.
+    ///
+    /// Chunks other than the header and tracks are skipped.
+    pub fn read<R: Read>(mut reader: R) -> Result<Smf, SmfError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Smf::parse(&bytes)
+    }
+
+    /// Parse a Standard MIDI File from its bytes.
+    pub fn parse(bytes: &[u8]) -> Result<Smf, SmfError> {
+        let mut cursor = Cursor(bytes);
+        let header = cursor.chunk(b"MThd")?;
+        let mut header = Cursor(header.ok_or(SmfError::Invalid("missing header chunk"))?);
+        let format = match header.u16()? {
+            0 => SmfFormat::SingleTrack,
+            1 => SmfFormat::MultiTrack,
+            _ => return Err(SmfError::Invalid("only formats 0 and 1 are supported")),
+        };
+        let n_tracks = header.u16()?;
+        let ticks_per_quarter = header.u16()?;
+        if ticks_per_quarter & 0x8000 != 0 || ticks_per_quarter == 0 {
+            return Err(SmfError::Invalid(
+                "only metrical time divisions are supported",
+            ));
+        }
+        let mut tracks = Vec::with_capacity(usize::from(n_tracks));
+        while tracks.len() < usize::from(n_tracks) {
+            if let Some(track) = cursor.chunk(b"MTrk")? {
+                tracks.push(parse_track(track)?);
+            }
+        }
+        if format == SmfFormat::SingleTrack && tracks.len() != 1 {
+            return Err(SmfError::Invalid(
+                "format 0 files must have exactly one track",
+            ));
+        }
+        Ok(Smf {
+            format,
+            ticks_per_quarter,
+            tracks,
+        })
+    }
+
+    /// Write the file.
+    ///
+    /// Events are written in the order of their ticks, without running status.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), SmfError> {
+        if self.format == SmfFormat::SingleTrack && self.tracks.len() != 1 {
+            return Err(SmfError::Invalid(
+                "format 0 files must have exactly one track",
+            ));
+        }
+        if self.ticks_per_quarter & 0x8000 != 0 || self.ticks_per_quarter == 0 {
+            return Err(SmfError::Invalid(
+                "only metrical time divisions are supported",
+            ));
+        }
+        let n_tracks: u16 =
+            u16::try_from(self.tracks.len()).map_err(|_| SmfError::Invalid("too many tracks"))?;
+        let format: u16 = match self.format {
+            SmfFormat::SingleTrack => 0,
+            SmfFormat::MultiTrack => 1,
+        };
+        writer.write_all(b"MThd")?;
+        writer.write_all(&6u32.to_be_bytes())?;
+        writer.write_all(&format.to_be_bytes())?;
+        writer.write_all(&n_tracks.to_be_bytes())?;
+        writer.write_all(&self.ticks_per_quarter.to_be_bytes())?;
+        for track in &self.tracks {
+            let track = encode_track(track)?;
+            let len =
+                u32::try_from(track.len()).map_err(|_| SmfError::Invalid("track too long"))?;
+            writer.write_all(b"MTrk")?;
+            writer.write_all(&len.to_be_bytes())?;
+            writer.write_all(&track)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SmfError> {
+        if self.0.len() < n {
+            return Err(SmfError::Invalid("unexpected end of data"));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, SmfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SmfError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SmfError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn var_len(&mut self) -> Result<u32, SmfError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let b = self.u8()?;
+            value = value << 7 | u32::from(b & 0x7F);
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SmfError::Invalid("variable length quantity is too long"))
+    }
+
+    /// Read the next chunk and return its data if it has the type `kind`.
+    fn chunk(&mut self, kind: &[u8; 4]) -> Result<Option<&'a [u8]>, SmfError> {
+        let chunk_kind = self.take(4)?;
+        let len = self.u32()? as usize;
+        let data = self.take(len)?;
+        Ok(if chunk_kind == kind { Some(data) } else { None })
+    }
+}
+
+fn parse_track(data: &[u8]) -> Result<Vec<SmfEvent>, SmfError> {
+    let mut cursor = Cursor(data);
+    let mut events = Vec::new();
+    let mut tick = 0u64;
+    let mut running_status = None;
+    while !cursor.0.is_empty() {
+        tick += u64::from(cursor.var_len()?);
+        let first = cursor.u8()?;
+        let data = match first {
+            0xFF => {
+                running_status = None;
+                let kind = cursor.u8()?;
+                let len = cursor.var_len()? as usize;
+                let data = cursor.take(len)?;
+                match kind {
+                    END_OF_TRACK => break,
+                    SET_TEMPO if len == 3 => SmfEventData::Tempo(
+                        u32::from(data[0]) << 16 | u32::from(data[1]) << 8 | u32::from(data[2]),
+                    ),
+                    _ => SmfEventData::Meta {
+                        kind,
+                        data: data.to_vec(),
+                    },
+                }
+            }
+            0xF0 => {
+                running_status = None;
+                let len = cursor.var_len()? as usize;
+                let mut message = vec![0xF0];
+                message.extend_from_slice(cursor.take(len)?);
+                SmfEventData::Midi(message)
+            }
+            0xF7 => {
+                running_status = None;
+                let len = cursor.var_len()? as usize;
+                SmfEventData::Midi(cursor.take(len)?.to_vec())
+            }
+            _ => {
+                let (status, first_data) = if first & 0x80 != 0 {
+                    running_status = Some(first);
+                    (first, None)
+                } else {
+                    let status =
+                        running_status.ok_or(SmfError::Invalid("data byte without status"))?;
+                    (status, Some(first))
+                };
+                let len = match status & 0xF0 {
+                    0xC0 | 0xD0 => 1,
+                    0x80..=0xE0 => 2,
+                    _ => return Err(SmfError::Invalid("unexpected system message")),
+                };
+                let mut message = vec![status];
+                message.extend(first_data);
+                let remaining = len - (message.len() - 1);
+                message.extend_from_slice(cursor.take(remaining)?);
+                SmfEventData::Midi(message)
+            }
+        };
+        events.push(SmfEvent { tick, data });
+    }
+    Ok(events)
+}
+
+fn encode_var_len(out: &mut Vec<u8>, value: u32) {
+    let mut groups = [0u8; 5];
+    let mut n = 0;
+    let mut value = value;
+    loop {
+        groups[n] = (value & 0x7F) as u8;
+        n += 1;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    for i in (0..n).rev() {
+        let continuation = if i > 0 { 0x80 } else { 0 };
+        out.push(groups[i] | continuation);
+    }
+}
+
+fn encode_len(out: &mut Vec<u8>, len: usize) -> Result<(), SmfError> {
+    let len = u32::try_from(len)
+        .ok()
+        .filter(|&len| len < 1 << 28)
+        .ok_or(SmfError::Invalid("event too long"))?;
+    encode_var_len(out, len);
+    Ok(())
+}
+
+fn encode_track(track: &[SmfEvent]) -> Result<Vec<u8>, SmfError> {
+    let mut events: Vec<&SmfEvent> = track.iter().collect();
+    // A stable sort keeps the order of events with the same tick.
+    events.sort_by_key(|event| event.tick);
+    let mut out = Vec::new();
+    let mut tick = 0;
+    for event in events {
+        let delta = u32::try_from(event.tick - tick)
+            .ok()
+            .filter(|&delta| delta < 1 << 28)
+            .ok_or(SmfError::Invalid("time between events is too long"))?;
+        encode_var_len(&mut out, delta);
+        tick = event.tick;
+        match &event.data {
+            SmfEventData::Midi(bytes) => match bytes.split_first() {
+                Some((0xF0, rest)) => {
+                    out.push(0xF0);
+                    encode_len(&mut out, rest.len())?;
+                    out.extend_from_slice(rest);
+                }
+                Some((status, _)) if *status >= 0x80 && *status < 0xF0 => {
+                    out.extend_from_slice(bytes)
+                }
+                _ => {
+                    out.push(0xF7);
+                    encode_len(&mut out, bytes.len())?;
+                    out.extend_from_slice(bytes);
+                }
+            },
+            SmfEventData::Tempo(tempo) => {
+                if *tempo >= 1 << 24 {
+                    return Err(SmfError::Invalid("tempo out of range"));
+                }
+                out.extend_from_slice(&[0xFF, SET_TEMPO, 3]);
+                out.extend_from_slice(&tempo.to_be_bytes()[1..]);
+            }
+            SmfEventData::Meta { kind, data } => {
+                out.extend_from_slice(&[0xFF, *kind]);
+                encode_len(&mut out, data.len())?;
+                out.extend_from_slice(data);
+            }
+        }
+    }
+    out.extend_from_slice(&[0, 0xFF, END_OF_TRACK, 0]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tick: u64, data: SmfEventData) -> SmfEvent {
+        SmfEvent { tick, data }
+    }
+
+    #[test]
+    fn variable_length_quantities_roundtrip() {
+        for &value in &[0, 0x40, 0x7F, 0x80, 0x2000, 0x3FFF, 0x4000, 0x0FFF_FFFF] {
+            let mut out = Vec::new();
+            encode_var_len(&mut out, value);
+            assert_eq!(Cursor(&out).var_len().unwrap(), value);
+        }
+        let mut out = Vec::new();
+        encode_var_len(&mut out, 0x80);
+        assert_eq!(out, [0x81, 0x00]);
+    }
+
+    #[test]
+    fn files_roundtrip() {
+        let smf = Smf {
+            format: SmfFormat::MultiTrack,
+            ticks_per_quarter: 960,
+            tracks: vec![
+                vec![
+                    event(0, SmfEventData::Tempo(DEFAULT_TEMPO)),
+                    event(
+                        0,
+                        SmfEventData::Meta {
+                            kind: 0x03,
+                            data: b"tempo".to_vec(),
+                        },
+                    ),
+                ],
+                vec![
+                    event(0, SmfEventData::Midi(vec![0x90, 60, 100])),
+                    event(480, SmfEventData::Midi(vec![0xC1, 5])),
+                    event(960, SmfEventData::Midi(vec![0x80, 60, 0])),
+                    event(20_000, SmfEventData::Midi(vec![0xF0, 0x7E, 0x01, 0xF7])),
+                ],
+            ],
+        };
+        let mut bytes = Vec::new();
+        smf.write(&mut bytes).unwrap();
+        assert_eq!(Smf::parse(&bytes).unwrap(), smf);
+    }
+
+    #[test]
+    fn running_status_is_expanded() {
+        let bytes = [
+            b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96, //
+            b'M', b'T', b'r', b'k', 0, 0, 0, 11, //
+            0, 0x90, 60, 100, //
+            10, 62, 100, //
+            0, 0xFF, 0x2F, 0,
+        ];
+        let smf = Smf::parse(&bytes).unwrap();
+        assert_eq!(smf.format, SmfFormat::SingleTrack);
+        assert_eq!(smf.ticks_per_quarter, 96);
+        assert_eq!(
+            smf.tracks,
+            vec![vec![
+                event(0, SmfEventData::Midi(vec![0x90, 60, 100])),
+                event(10, SmfEventData::Midi(vec![0x90, 62, 100])),
+            ]]
+        );
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(matches!(Smf::parse(b"MThd"), Err(SmfError::Invalid(_))));
+        let smpte = [
+            b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 0, 0xE7, 40, //
+        ];
+        assert!(matches!(Smf::parse(&smpte), Err(SmfError::Invalid(_))));
+        let format_0 = Smf {
+            format: SmfFormat::SingleTrack,
+            ticks_per_quarter: 96,
+            tracks: vec![Vec::new(), Vec::new()],
+        };
+        assert!(matches!(
+            format_0.write(Vec::new()),
+            Err(SmfError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Playback of a Standard MIDI File on a midi output port, following the JACK transport.
+//!
+//! An [`SmfPlayer`] plays the events of all tracks of an [`Smf`] while the transport is rolling.
+//! If the transport position has valid BBT data, events are placed by their position in quarter
+//! notes at the tempo in `TransportBBT`. Otherwise they are placed by their time in seconds,
+//! according to the tempo events of the file. Notes that are held when the transport stops or is
+//! relocated are released.
+//!
+//! ```no_run
+//! use jack::contrib::smf::{play::SmfPlayer, Smf};
+//!
+//! let file = std::fs::File::open("song.mid").unwrap();
+//! let smf = Smf::read(std::io::BufReader::new(file)).unwrap();
+//! let (client, _status) =
+//!     jack::Client::new("smf_play", jack::ClientOptions::default()).unwrap();
+//! let mut port = client
+//!     .register_port("out", jack::MidiOut::default())
+//!     .unwrap();
+//! let mut player = SmfPlayer::new(&smf);
+//! let handler = jack::contrib::ClosureProcessHandler::new(move |client, ps| {
+//!     let mut writer = port.writer(ps);
+//!     player.play(client, ps, &mut writer).unwrap();
+//!     jack::Control::Continue
+//! });
+//! let active_client = client.activate_async((), handler).unwrap();
+//! active_client.as_client().transport().start().unwrap();
+//! ```
+use super::{Smf, SmfEventData, DEFAULT_TEMPO};
+use crate::contrib::transport_sync::NextCycle;
+use crate::{
+    Client, Error, Frames, MidiMessage, MidiWriter, ProcessScope, RawMidi, TransportPosition,
+    TransportState,
+};
+
+#[derive(Debug)]
+struct PlayerEvent {
+    tick: u64,
+    seconds: f64,
+    bytes: Vec<u8>,
+}
+
+/// Plays a Standard MIDI File following the JACK transport.
+#[derive(Debug)]
+pub struct SmfPlayer {
+    events: Vec<PlayerEvent>,
+    ticks_per_quarter: f64,
+    held_notes: [u128; 16],
+    next_cycle: NextCycle,
+}
+
+impl SmfPlayer {
+    /// Create a player for the midi events of all tracks of `smf`.
+    pub fn new(smf: &Smf) -> SmfPlayer {
+        let mut tempos: Vec<(u64, u32)> = Vec::new();
+        let mut events: Vec<(u64, Vec<u8>)> = Vec::new();
+        for track in &smf.tracks {
+            for event in track {
+                match &event.data {
+                    SmfEventData::Midi(bytes) => events.push((event.tick, bytes.clone())),
+                    SmfEventData::Tempo(tempo) => tempos.push((event.tick, *tempo)),
+                    SmfEventData::Meta { .. } => {}
+                }
+            }
+        }
+        // Stable sorts keep the order of simultaneous events within a track.
+        tempos.sort_by_key(|(tick, _)| *tick);
+        events.sort_by_key(|(tick, _)| *tick);
+
+        let ticks_per_quarter = f64::from(smf.ticks_per_quarter);
+        let mut tempos = tempos.into_iter().peekable();
+        let (mut tempo_tick, mut tempo_seconds, mut tempo) = (0, 0.0, DEFAULT_TEMPO);
+        let seconds_between = |from: u64, to: u64, tempo: u32| {
+            (to - from) as f64 * f64::from(tempo) / 1_000_000.0 / ticks_per_quarter
+        };
+        let events = events
+            .into_iter()
+            .map(|(tick, bytes)| {
+                while let Some(&(change_tick, new_tempo)) = tempos.peek() {
+                    if change_tick > tick {
+                        break;
+                    }
+                    tempo_seconds += seconds_between(tempo_tick, change_tick, tempo);
+                    tempo_tick = change_tick;
+                    tempo = new_tempo;
+                    tempos.next();
+                }
+                let seconds = tempo_seconds + seconds_between(tempo_tick, tick, tempo);
+                PlayerEvent {
+                    tick,
+                    seconds,
+                    bytes,
+                }
+            })
+            .collect();
+        SmfPlayer {
+            events,
+            ticks_per_quarter,
+            held_notes: [0; 16],
+            next_cycle: NextCycle::default(),
+        }
+    }
+
+    /// Write the events that fall into the current cycle into `writer` and return how many were
+    /// written.
+    ///
+    /// Nothing is played unless the transport is rolling.
+    pub fn play(
+        &mut self,
+        client: &Client,
+        ps: &ProcessScope,
+        writer: &mut MidiWriter,
+    ) -> Result<usize, Error> {
+        let transport = client.transport().query()?;
+        if transport.state != TransportState::Rolling {
+            if self.next_cycle.reset() {
+                self.release_notes(writer);
+            }
+            return Ok(0);
+        }
+        let n_frames = ps.n_frames();
+        if self.next_cycle.advance(transport.pos.frame(), n_frames) {
+            self.release_notes(writer);
+        }
+
+        let sample_rate = f64::from(client.sample_rate());
+        let (start, end, key): (f64, f64, fn(&PlayerEvent) -> f64) =
+            match quarter_window(&transport.pos, n_frames, sample_rate) {
+                Some((start, end)) => (
+                    start * self.ticks_per_quarter,
+                    end * self.ticks_per_quarter,
+                    |event| event.tick as f64,
+                ),
+                None => (
+                    f64::from(transport.pos.frame()) / sample_rate,
+                    f64::from(transport.pos.frame().wrapping_add(n_frames)) / sample_rate,
+                    |event| event.seconds,
+                ),
+            };
+        if end <= start {
+            return Ok(0);
+        }
+
+        let first = self.events.partition_point(|event| key(event) < start);
+        let mut written = 0;
+        for event in &self.events[first..] {
+            let position = key(event);
+            if position >= end {
+                break;
+            }
+            let offset = ((position - start) / (end - start) * f64::from(n_frames)) as Frames;
+            let raw = RawMidi {
+                time: offset.min(n_frames - 1),
+                bytes: &event.bytes,
+            };
+            if writer.write(&raw).is_ok() {
+                track_note(&mut self.held_notes, &event.bytes);
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Send a note off for every note that is held at the start of the cycle.
+    fn release_notes(&mut self, writer: &mut MidiWriter) {
+        for (channel, notes) in self.held_notes.iter_mut().enumerate() {
+            for note in 0..128 {
+                if *notes & 1 << note == 0 {
+                    continue;
+                }
+                let note_off = MidiMessage::NoteOff {
+                    channel: channel as u8,
+                    note,
+                    velocity: 0,
+                };
+                // A note that can not be released now is released by the next stop or locate.
+                if writer.write_message(0, &note_off).is_ok() {
+                    *notes &= !(1 << note);
+                }
+            }
+        }
+    }
+}
+
+/// The start and end of the cycle in quarter notes since the start of the song, if the transport
+/// has BBT data.
+fn quarter_window(
+    pos: &TransportPosition,
+    n_frames: Frames,
+    sample_rate: f64,
+) -> Option<(f64, f64)> {
+    let bbt = pos.bbt()?;
+    let start = bbt.quarter_notes();
+    Some((start, start + bbt.quarter_notes_in(n_frames, sample_rate)))
+}
+
+fn track_note(held_notes: &mut [u128; 16], bytes: &[u8]) {
+    match MidiMessage::parse(bytes) {
+        Ok(MidiMessage::NoteOn {
+            channel,
+            note,
+            velocity,
+        }) if velocity > 0 => held_notes[usize::from(channel)] |= 1 << note,
+        Ok(MidiMessage::NoteOn { channel, note, .. })
+        | Ok(MidiMessage::NoteOff { channel, note, .. }) => {
+            held_notes[usize::from(channel)] &= !(1 << note)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contrib::smf::{SmfEvent, SmfFormat};
+
+    #[test]
+    fn event_times_follow_the_tempo_map() {
+        let note = |tick| SmfEvent {
+            tick,
+            data: SmfEventData::Midi(vec![0x90, 60, 100]),
+        };
+        let tempo = |tick, tempo| SmfEvent {
+            tick,
+            data: SmfEventData::Tempo(tempo),
+        };
+        let smf = Smf {
+            format: SmfFormat::MultiTrack,
+            ticks_per_quarter: 480,
+            tracks: vec![
+                vec![tempo(960, 250_000)],
+                vec![note(480), note(960), note(1440)],
+            ],
+        };
+        let player = SmfPlayer::new(&smf);
+        let seconds: Vec<f64> = player.events.iter().map(|event| event.seconds).collect();
+        assert_eq!(seconds, [0.5, 1.0, 1.25]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Recording of a midi input port into a Standard MIDI File.
+//!
+//! [`channel`] creates an [`SmfRecorderInput`], which copies the events of a `MidiIn` port into a
+//! ring buffer during the process cycle, and an [`SmfRecorder`], which collects them on another
+//! thread. Events are stamped with their frame time, so their timing is sample accurate until it
+//! is converted to ticks by [`SmfRecorder::to_smf`].
+//!
+//! ```no_run
+//! use jack::contrib::smf::{record, SmfFormat, DEFAULT_TEMPO};
+//!
+//! let (client, _status) =
+//!     jack::Client::new("smf_record", jack::ClientOptions::default()).unwrap();
+//! let port = client
+//!     .register_port("in", jack::MidiIn::default())
+//!     .unwrap();
+//! let (mut recorder, mut input) = record::channel(1 << 16).unwrap();
+//! let handler = jack::contrib::ClosureProcessHandler::new(move |_, ps| {
+//!     input.record(ps, &port);
+//!     jack::Control::Continue
+//! });
+//! let active_client = client.activate_async((), handler).unwrap();
+//!
+//! for _ in 0..100 {
+//!     std::thread::sleep(std::time::Duration::from_millis(100));
+//!     recorder.collect();
+//! }
+//! let sample_rate = active_client.as_client().sample_rate();
+//! let smf = recorder.to_smf(SmfFormat::SingleTrack, sample_rate, 960, DEFAULT_TEMPO);
+//! smf.write(std::fs::File::create("recording.mid").unwrap())
+//!     .unwrap();
+//! ```
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::{Smf, SmfEvent, SmfEventData, SmfFormat};
+use crate::{
+    Error, Frames, MidiIn, Port, ProcessScope, RingBuffer, RingBufferReader, RingBufferWriter,
+};
+
+/// The size of the header that precedes the bytes of each event in the ring buffer: the frame
+/// time and the number of bytes. A header without bytes marks the start of the recording.
+const HEADER_SIZE: usize = 4 + 4;
+
+/// Create a recorder and its input, connected by a ring buffer of `capacity` bytes.
+///
+/// Each event takes up its size plus 8 bytes of the capacity until it is collected.
+pub fn channel(capacity: usize) -> Result<(SmfRecorder, SmfRecorderInput), Error> {
+    let mut ringbuffer = RingBuffer::new(capacity)?;
+    ringbuffer.mlock();
+    let (reader, writer) = ringbuffer.into_reader_writer();
+    let dropped = Arc::new(AtomicU64::new(0));
+    let recorder = SmfRecorder {
+        reader,
+        dropped: dropped.clone(),
+        last_frame: None,
+        frames: 0,
+        events: Vec::new(),
+    };
+    let input = SmfRecorderInput {
+        writer,
+        dropped,
+        is_started: false,
+    };
+    Ok((recorder, input))
+}
+
+/// The real-time half of a recorder, used in the process callback.
+///
+/// Created with [`channel`].
+pub struct SmfRecorderInput {
+    writer: RingBufferWriter,
+    dropped: Arc<AtomicU64>,
+    is_started: bool,
+}
+
+impl SmfRecorderInput {
+    /// Copy all events of `port` in the current cycle to the recorder. The recording starts at
+    /// the first cycle this is called in.
+    ///
+    /// Events that do not fit into the ring buffer are dropped and counted, see
+    /// [`SmfRecorder::dropped_events`]. This does not allocate or block.
+    pub fn record(&mut self, ps: &ProcessScope, port: &Port<MidiIn>) {
+        let cycle_start = ps.last_frame_time();
+        if !self.is_started {
+            self.is_started = self.push(cycle_start, &[]);
+        }
+        for event in port.iter(ps) {
+            if !self.is_started || !self.push(cycle_start.wrapping_add(event.time), event.bytes) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn push(&mut self, frame: Frames, bytes: &[u8]) -> bool {
+        if self.writer.space() < HEADER_SIZE + bytes.len() {
+            return false;
+        }
+        let mut header = [0; HEADER_SIZE];
+        header[..4].copy_from_slice(&frame.to_le_bytes());
+        header[4..].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        self.writer.write_buffer(&header);
+        self.writer.write_buffer(bytes);
+        true
+    }
+}
+
+/// The non real-time half of a recorder, which collects the recorded events.
+///
+/// Created with [`channel`].
+pub struct SmfRecorder {
+    reader: RingBufferReader,
+    dropped: Arc<AtomicU64>,
+    last_frame: Option<Frames>,
+    frames: u64,
+    events: Vec<(u64, Vec<u8>)>,
+}
+
+impl SmfRecorder {
+    /// Move the events from the ring buffer into the recording and return how many were moved.
+    ///
+    /// This must be called often enough for the ring buffer not to fill up.
+    pub fn collect(&mut self) -> usize {
+        let mut collected = 0;
+        let mut header = [0; HEADER_SIZE];
+        while self.reader.peek(&mut header) == HEADER_SIZE {
+            let frame = Frames::from_le_bytes([header[0], header[1], header[2], header[3]]);
+            let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            if self.reader.space() < HEADER_SIZE + len {
+                // The input has not finished writing the event.
+                break;
+            }
+            self.reader.advance(HEADER_SIZE);
+            // Frame times wrap around, count the frames since the start of the recording instead.
+            if let Some(last_frame) = self.last_frame {
+                self.frames += u64::from(frame.wrapping_sub(last_frame));
+            }
+            self.last_frame = Some(frame);
+            if len > 0 {
+                let mut bytes = vec![0; len];
+                self.reader.read_buffer(&mut bytes);
+                self.events.push((self.frames, bytes));
+                collected += 1;
+            }
+        }
+        collected
+    }
+
+    /// The number of collected events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events have been collected.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The number of events that were dropped because the ring buffer was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Convert the collected events into a Standard MIDI File.
+    ///
+    /// Frames are converted to ticks at `sample_rate` with a constant `tempo`, in microseconds per
+    /// quarter note, which is stored as the first event. Event times are rounded to the nearest
+    /// tick. A [`SmfFormat::MultiTrack`] file stores the tempo in the first and the events in the
+    /// second track.
+    pub fn to_smf(
+        &self,
+        format: SmfFormat,
+        sample_rate: u32,
+        ticks_per_quarter: u16,
+        tempo: u32,
+    ) -> Smf {
+        let ticks_per_frame = f64::from(ticks_per_quarter) * 1_000_000.0
+            / (f64::from(sample_rate) * f64::from(tempo));
+        let tempo = SmfEvent {
+            tick: 0,
+            data: SmfEventData::Tempo(tempo),
+        };
+        let events = self.events.iter().map(|(frame, bytes)| SmfEvent {
+            tick: (*frame as f64 * ticks_per_frame).round() as u64,
+            data: SmfEventData::Midi(bytes.clone()),
+        });
+        let tracks = match format {
+            SmfFormat::SingleTrack => vec![std::iter::once(tempo).chain(events).collect()],
+            SmfFormat::MultiTrack => vec![vec![tempo], events.collect()],
+        };
+        Smf {
+            format,
+            ticks_per_quarter,
+            tracks,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Building blocks of the contrib modules that follow or drive the JACK transport in the process
+//! callback: [`midi_clock`](super::midi_clock), [`mtc`](super::mtc) and
+//! [`smf::play`](super::smf::play).
+use crate::{Error, Frames, MidiMessage, MidiWriter, Transport};
+
+/// A transport command issued while following an external clock.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum TransportCommand {
+    Start,
+    Stop,
+    Locate(Frames),
+    LocateAndStart(Frames),
+}
+
+impl TransportCommand {
+    /// Issue the command to `transport`.
+    pub(crate) fn apply(self, transport: &Transport) -> Result<(), Error> {
+        match self {
+            TransportCommand::Start => transport.start(),
+            TransportCommand::Stop => transport.stop(),
+            TransportCommand::Locate(frame) => transport.locate(frame),
+            TransportCommand::LocateAndStart(frame) => {
+                transport.locate(frame)?;
+                transport.start()
+            }
+        }
+    }
+}
+
+/// Detects relocations of the transport between consecutive cycles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NextCycle {
+    /// The transport frame at which the next cycle starts if the transport is not relocated.
+    /// `None` if no cycle was recorded since the last reset.
+    frame: Option<Frames>,
+}
+
+impl NextCycle {
+    /// Record a cycle of `n_frames` that starts at transport `frame`. Returns `true` if the
+    /// previously recorded cycle did not end at `frame`, i.e. the transport was relocated.
+    pub(crate) fn advance(&mut self, frame: Frames, n_frames: Frames) -> bool {
+        let is_relocated = self.frame.is_some_and(|next| next != frame);
+        self.frame = Some(frame.wrapping_add(n_frames));
+        is_relocated
+    }
+
+    /// Forget the recorded cycle, e.g. because the transport stopped. Returns `true` if a cycle
+    /// was recorded.
+    pub(crate) fn reset(&mut self) -> bool {
+        self.frame.take().is_some()
+    }
+}
+
+/// Writes the messages of a cycle into a `MidiWriter`, counting them. Once a message can not be
+/// written, the following ones are dropped and the error is kept.
+pub(crate) struct CycleWriter<'w, 'a> {
+    writer: &'w mut MidiWriter<'a>,
+    res: Result<usize, Error>,
+}
+
+impl<'w, 'a> CycleWriter<'w, 'a> {
+    pub(crate) fn new(writer: &'w mut MidiWriter<'a>) -> Self {
+        CycleWriter { writer, res: Ok(0) }
+    }
+
+    pub(crate) fn write(&mut self, time: Frames, message: &MidiMessage) {
+        if let Ok(written) = self.res.as_mut() {
+            match self.writer.write_message(time, message) {
+                Ok(()) => *written += 1,
+                Err(err) => self.res = Err(err),
+            }
+        }
+    }
+
+    /// The number of messages written, or the first error.
+    pub(crate) fn finish(self) -> Result<usize, Error> {
+        self.res
+    }
+}
@@ -0,0 +1,83 @@
+//! Xrun statistics collection.
+//!
+//! [`XrunMonitor`] is a `NotificationHandler` that records every xrun reported by JACK. It is
+//! cheap to clone and all clones share the same statistics, so one clone can be given to
+//! `Client::activate_async` while another is used to take snapshots.
+//!
+//! ```no_run
+//! let (client, _status) =
+//!     jack::Client::new("xrun_monitor", jack::ClientOptions::default()).unwrap();
+//! let monitor = jack::contrib::xrun::XrunMonitor::new();
+//! let _active_client = client.activate_async(monitor.clone(), ()).unwrap();
+//!
+//! loop {
+//!     std::thread::sleep(std::time::Duration::from_secs(1));
+//!     println!("{:?}", monitor.snapshot());
+//! }
+//! ```
+use std::sync::{Arc, Mutex};
+
+use crate::contrib::lock::lock;
+use crate::{Client, Control, NotificationHandler, Time};
+
+/// A snapshot of the xruns recorded by an [`XrunMonitor`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct XrunStats {
+    /// The number of xruns.
+    pub count: u64,
+    /// The delay of the most recent xrun in microseconds.
+    pub last_delay_usecs: f32,
+    /// The largest delay of all recorded xruns in microseconds.
+    pub max_delay_usecs: f32,
+    /// The time of the most recent xrun, as returned by `Client::time`. `None` if there have been
+    /// no xruns.
+    pub last_xrun_time: Option<Time>,
+}
+
+/// A `NotificationHandler` that records xrun statistics.
+#[derive(Clone, Debug, Default)]
+pub struct XrunMonitor {
+    stats: Arc<Mutex<XrunStats>>,
+}
+
+impl XrunMonitor {
+    /// Create a monitor without any recorded xruns.
+    pub fn new() -> XrunMonitor {
+        XrunMonitor::default()
+    }
+
+    /// Record an xrun that was just reported to `client`.
+    ///
+    /// This is called by the `NotificationHandler` implementation. Call it from
+    /// `NotificationHandler::xrun` to use the monitor together with another notification handler.
+    pub fn record(&self, client: &Client) {
+        let delay = client.xrun_delayed_usecs();
+        let time = client.time();
+        let mut stats = self.lock();
+        stats.count += 1;
+        stats.last_delay_usecs = delay;
+        stats.max_delay_usecs = stats.max_delay_usecs.max(delay);
+        stats.last_xrun_time = Some(time);
+    }
+
+    /// Get the current statistics.
+    pub fn snapshot(&self) -> XrunStats {
+        *self.lock()
+    }
+
+    /// Clear all recorded statistics.
+    pub fn reset(&self) {
+        *self.lock() = XrunStats::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, XrunStats> {
+        lock(&self.stats)
+    }
+}
+
+impl NotificationHandler for XrunMonitor {
+    fn xrun(&mut self, client: &Client) -> Control {
+        self.record(client);
+        Control::Continue
+    }
+}
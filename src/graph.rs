@@ -0,0 +1,421 @@
+//! Snapshots of the clients, ports and connections of a JACK server.
+use jack_sys as j;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::properties::get_property;
+use crate::{Client, Frames, LatencyType, Port, PortFlags, SignalType, Uuid, METADATA_PRETTY_NAME};
+
+/// A snapshot of the clients, ports and connections of a JACK server, taken with
+/// [`Client::graph`].
+///
+/// Clients, ports and connections are ordered by name, so snapshots of the same graph compare
+/// equal.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Graph {
+    /// The clients, by name.
+    pub clients: BTreeMap<String, GraphClient>,
+    /// All ports, by full name.
+    pub ports: BTreeMap<String, GraphPort>,
+    /// All connections between ports.
+    pub connections: BTreeSet<Connection>,
+}
+
+/// A client in a [`Graph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphClient {
+    pub name: String,
+    /// `None` if the client was gone before its uuid could be looked up, or if a
+    /// `GraphTracker` did not look it up yet, see `GraphTracker::resolve_clients`.
+    pub uuid: Option<Uuid>,
+    /// The pretty name from the client's metadata.
+    pub pretty_name: Option<String>,
+}
+
+/// A port in a [`Graph`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphPort {
+    /// The full name of the port, including the "client_name:" prefix.
+    pub name: String,
+    /// The name of the client that owns the port.
+    pub client: String,
+    /// The port type, such as `"32 bit float mono audio"`.
+    pub port_type: String,
+    pub flags: PortFlags,
+    pub aliases: Vec<String>,
+    pub uuid: Uuid,
+    /// The minimum and maximum capture latency in frames.
+    pub capture_latency: (Frames, Frames),
+    /// The minimum and maximum playback latency in frames.
+    pub playback_latency: (Frames, Frames),
+    /// The pretty name from the port's metadata.
+    pub pretty_name: Option<String>,
+    /// The signal type from the port's metadata, see [`Port::signal_type`].
+    pub signal_type: Option<SignalType>,
+}
+
+/// A connection from an output port to an input port, by their full names.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Connection {
+    pub output: String,
+    pub input: String,
+}
+
+impl GraphClient {
+    pub(crate) fn new(client: &Client, name: &str) -> GraphClient {
+        let uuid = client.uuid_of_client_by_name(name);
+        GraphClient {
+            name: name.to_string(),
+            uuid,
+            pretty_name: uuid
+                .and_then(|uuid| get_property(uuid, METADATA_PRETTY_NAME))
+                .map(|p| p.value().to_string()),
+        }
+    }
+}
+
+impl GraphPort {
+    /// Describe `port`. `None` if the port is gone.
+    pub(crate) fn new<PS>(port: &Port<PS>) -> Option<GraphPort> {
+        let name = port.name().ok()?;
+        let client = name.split_once(':')?.0.to_string();
+        Some(GraphPort {
+            client,
+            port_type: port.port_type().ok()?,
+            flags: port.flags(),
+            aliases: port.aliases().ok()?,
+            uuid: port.uuid().ok()?,
+            capture_latency: port.get_latency_range(LatencyType::Capture),
+            playback_latency: port.get_latency_range(LatencyType::Playback),
+            pretty_name: port.pretty_name().ok()?,
+            // A malformed signal type is treated like a missing one.
+            signal_type: port.signal_type().unwrap_or(None),
+            name,
+        })
+    }
+
+    /// The name of the port without the "client_name:" prefix.
+    pub fn short_name(&self) -> &str {
+        self.name
+            .strip_prefix(self.client.as_str())
+            .and_then(|name| name.strip_prefix(':'))
+            .unwrap_or(&self.name)
+    }
+
+    /// The color of the connections from this port in [`Graph::to_dot`].
+    fn dot_color(&self) -> &'static str {
+        if self.port_type == j::FLOAT_MONO_AUDIO {
+            match self.signal_type {
+                Some(SignalType::Cv) => "darkorange",
+                _ => "blue",
+            }
+        } else if self.port_type == j::RAW_MIDI_TYPE {
+            "red"
+        } else {
+            "gray"
+        }
+    }
+}
+
+impl Connection {
+    /// Create a connection from `output` to `input`.
+    pub fn new(output: &str, input: &str) -> Connection {
+        Connection {
+            output: output.to_string(),
+            input: input.to_string(),
+        }
+    }
+}
+
+/// The differences between two [`Graph`]s, as returned by [`Graph::diff`].
+///
+/// Clients and ports are identified by name, so a renamed port shows up as removed under its old
+/// name and added under its new one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_clients: Vec<String>,
+    pub removed_clients: Vec<String>,
+    pub added_ports: Vec<String>,
+    pub removed_ports: Vec<String>,
+    /// Ports that exist in both graphs but whose type, flags, aliases, uuid, latency or metadata
+    /// changed.
+    pub changed_ports: Vec<String>,
+    pub added_connections: Vec<Connection>,
+    pub removed_connections: Vec<Connection>,
+}
+
+impl GraphDiff {
+    /// Returns `true` if the graphs are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_clients.is_empty()
+            && self.removed_clients.is_empty()
+            && self.added_ports.is_empty()
+            && self.removed_ports.is_empty()
+            && self.changed_ports.is_empty()
+            && self.added_connections.is_empty()
+            && self.removed_connections.is_empty()
+    }
+}
+
+impl Graph {
+    /// Compute what changed from `old` to `new`. All lists are sorted by name.
+    pub fn diff(old: &Graph, new: &Graph) -> GraphDiff {
+        GraphDiff {
+            added_clients: missing_keys(&new.clients, &old.clients),
+            removed_clients: missing_keys(&old.clients, &new.clients),
+            added_ports: missing_keys(&new.ports, &old.ports),
+            removed_ports: missing_keys(&old.ports, &new.ports),
+            changed_ports: new
+                .ports
+                .iter()
+                .filter(|(name, port)| old.ports.get(*name).is_some_and(|old| old != *port))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            added_connections: new
+                .connections
+                .difference(&old.connections)
+                .cloned()
+                .collect(),
+            removed_connections: old
+                .connections
+                .difference(&new.connections)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// The ports owned by the client named `client`.
+    pub fn client_ports<'a>(&'a self, client: &'a str) -> impl Iterator<Item = &'a GraphPort> {
+        self.ports
+            .values()
+            .filter(move |port| port.client == client)
+    }
+
+    /// The connections from or to the port named `port`.
+    pub fn port_connections<'a>(&'a self, port: &'a str) -> impl Iterator<Item = &'a Connection> {
+        self.connections
+            .iter()
+            .filter(move |connection| connection.output == port || connection.input == port)
+    }
+
+    /// Render the graph in the Graphviz DOT language, e.g. for `dot -Tsvg`.
+    ///
+    /// Each client is a cluster that groups its output ports and its input ports. Clients and
+    /// ports are labeled with their pretty names when they have one. Connections are colored by
+    /// the type of their output port: blue for audio, darkorange for CV, red for MIDI and gray for
+    /// anything else.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str("digraph jack {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=rounded];\n");
+        for (index, client) in self.clients.values().enumerate() {
+            let label = client.pretty_name.as_deref().unwrap_or(&client.name);
+            let _ = writeln!(dot, "    subgraph cluster_{index} {{");
+            let _ = writeln!(dot, "        label={};", dot_quote(label));
+            for (group, flag) in [
+                ("outputs", PortFlags::IS_OUTPUT),
+                ("inputs", PortFlags::IS_INPUT),
+            ] {
+                let mut ports = self
+                    .client_ports(&client.name)
+                    .filter(|port| port.flags.contains(flag))
+                    .peekable();
+                if ports.peek().is_none() {
+                    continue;
+                }
+                let _ = writeln!(dot, "        subgraph cluster_{index}_{group} {{");
+                let _ = writeln!(dot, "            label={group};");
+                for port in ports {
+                    let label = port.pretty_name.as_deref().unwrap_or(port.short_name());
+                    let _ = writeln!(
+                        dot,
+                        "            {} [label={}];",
+                        dot_quote(&port.name),
+                        dot_quote(label)
+                    );
+                }
+                dot.push_str("        }\n");
+            }
+            dot.push_str("    }\n");
+        }
+        for connection in &self.connections {
+            let color = self
+                .ports
+                .get(&connection.output)
+                .map_or("gray", GraphPort::dot_color);
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [color={color}];",
+                dot_quote(&connection.output),
+                dot_quote(&connection.input)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Quote `s` as a DOT string.
+fn dot_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn missing_keys<V>(from: &BTreeMap<String, V>, to: &BTreeMap<String, V>) -> Vec<String> {
+    from.keys()
+        .filter(|key| !to.contains_key(*key))
+        .cloned()
+        .collect()
+}
+
+impl Client {
+    /// Take a snapshot of all clients, ports and connections.
+    ///
+    /// JACK does not list clients, so only clients that own ports are included. The graph may
+    /// change while the snapshot is taken, ports that disappear in the meantime are left out.
+    ///
+    /// # Remarks
+    /// * Not realtime safe.
+    pub fn graph(&self) -> Graph {
+        let mut graph = Graph::default();
+        for name in self.ports(None, None, PortFlags::empty()) {
+            let (port, connections) = match self.port_by_name(&name) {
+                Some(port) => (GraphPort::new(&port), port.get_connections()),
+                None => continue,
+            };
+            let port = match port {
+                Some(port) => port,
+                None => continue,
+            };
+            if port.flags.contains(PortFlags::IS_OUTPUT) {
+                for input in connections {
+                    graph.connections.insert(Connection {
+                        output: name.clone(),
+                        input,
+                    });
+                }
+            }
+            if !graph.clients.contains_key(&port.client) {
+                let client = GraphClient::new(self, &port.client);
+                graph.clients.insert(port.client.clone(), client);
+            }
+            graph.ports.insert(name, port);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(name: &str, flags: PortFlags) -> GraphPort {
+        GraphPort {
+            name: name.to_string(),
+            client: name.split_once(':').unwrap().0.to_string(),
+            port_type: "32 bit float mono audio".to_string(),
+            flags,
+            aliases: Vec::new(),
+            uuid: Uuid::EMPTY,
+            capture_latency: (0, 0),
+            playback_latency: (0, 0),
+            pretty_name: None,
+            signal_type: None,
+        }
+    }
+
+    fn graph(ports: &[GraphPort], connections: &[(&str, &str)]) -> Graph {
+        let mut graph = Graph::default();
+        for port in ports {
+            graph.clients.insert(
+                port.client.clone(),
+                GraphClient {
+                    name: port.client.clone(),
+                    uuid: None,
+                    pretty_name: None,
+                },
+            );
+            graph.ports.insert(port.name.clone(), port.clone());
+        }
+        graph.connections = connections
+            .iter()
+            .map(|(output, input)| Connection::new(output, input))
+            .collect();
+        graph
+    }
+
+    #[test]
+    fn diff_lists_added_removed_and_changed_items() {
+        let out = port("a:out", PortFlags::IS_OUTPUT);
+        let input = port("b:in", PortFlags::IS_INPUT);
+        let mut slow_input = input.clone();
+        slow_input.playback_latency = (256, 256);
+        let old = graph(&[out.clone(), input], &[("a:out", "b:in")]);
+        let new = graph(
+            &[out, slow_input, port("c:in", PortFlags::IS_INPUT)],
+            &[("a:out", "c:in")],
+        );
+
+        assert!(Graph::diff(&old, &old).is_empty());
+        assert_eq!(
+            Graph::diff(&old, &new),
+            GraphDiff {
+                added_clients: vec!["c".to_string()],
+                added_ports: vec!["c:in".to_string()],
+                changed_ports: vec!["b:in".to_string()],
+                added_connections: vec![Connection::new("a:out", "c:in")],
+                removed_connections: vec![Connection::new("a:out", "b:in")],
+                ..GraphDiff::default()
+            }
+        );
+        let reverse = Graph::diff(&new, &old);
+        assert_eq!(reverse.removed_clients, ["c"]);
+        assert_eq!(reverse.removed_ports, ["c:in"]);
+        assert_eq!(new.client_ports("a").count(), 1);
+        assert_eq!(new.port_connections("c:in").count(), 1);
+    }
+
+    #[test]
+    fn to_dot_groups_ports_by_client_and_colors_connections() {
+        let mut cv = port("a:cv", PortFlags::IS_OUTPUT);
+        cv.signal_type = Some(SignalType::Cv);
+        cv.pretty_name = Some("Cutoff \"CV\"".to_string());
+        let mut midi = port("b:midi_in", PortFlags::IS_INPUT);
+        midi.port_type = "8 bit raw midi".to_string();
+        let mut graph = graph(
+            &[
+                port("a:out", PortFlags::IS_OUTPUT),
+                cv,
+                port("b:in", PortFlags::IS_INPUT),
+                midi,
+            ],
+            &[("a:out", "b:in"), ("a:cv", "b:in")],
+        );
+        graph.clients.get_mut("b").unwrap().pretty_name = Some("Synth".to_string());
+        let dot = graph.to_dot();
+
+        assert!(dot.starts_with("digraph jack {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("subgraph cluster_0 {\n        label=\"a\";"));
+        assert!(dot.contains("subgraph cluster_1 {\n        label=\"Synth\";"));
+        assert!(dot.contains("subgraph cluster_0_outputs {"));
+        assert!(!dot.contains("subgraph cluster_0_inputs {"));
+        assert!(dot.contains("\"a:cv\" [label=\"Cutoff \\\"CV\\\"\"];"));
+        assert!(dot.contains("\"b:midi_in\" [label=\"midi_in\"];"));
+        assert!(dot.contains("\"a:out\" -> \"b:in\" [color=blue];"));
+        assert!(dot.contains("\"a:cv\" -> \"b:in\" [color=darkorange];"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::server::ParameterValidationError;
 use crate::ClientStatus;
 
 /// An error that can occur in JACK.
@@ -10,6 +11,8 @@ pub enum Error {
     ClientDeactivationError,
     ClientError(ClientStatus),
     FreewheelError,
+    FreewheelTimeout,
+    TransportNotRolling,
     InvalidDeactivation,
     NotEnoughSpace,
     PortAliasError,
@@ -23,12 +26,39 @@ pub enum Error {
     PortMonitorError,
     PortNamingError,
     PortRegistrationError(String),
+    PortTieError,
+    PortUntieError,
     SetBufferSizeError,
     TimeError,
     WeakFunctionNotFound(&'static str),
     ClientIsNoLongerAlive,
     ClientPanicked,
     RingbufferCreateFailed,
+    ServerCreationError,
+    ServerOpenError,
+    ServerStartError,
+    ServerStopError,
+    ServerCloseError,
+    DriverNotFound(String),
+    SlaveDriverError(String),
+    InternalClientError(String),
+    InvalidParameterValue {
+        parameter: String,
+        reason: ParameterValidationError,
+    },
+    ParameterSetError(String),
+    TimebaseMasterExists,
+    NotTimebaseMaster,
+    InvalidUuid(String),
+    InvalidPropertyValue {
+        key: String,
+        reason: &'static str,
+    },
+    ThreadCreationError,
+    ThreadJoinError,
+    ThreadPanicked,
+    RealtimeSchedulingError,
+    InvalidMidiMessage(&'static str),
     UnknownError {
         error_code: libc::c_int,
     },
@@ -44,6 +74,8 @@ impl std::fmt::Display for Error {
             Error::ClientDeactivationError => write!(f, "client deactivation error"),
             Error::ClientError(status) => write!(f, "client error, status is {status:?}"),
             Error::FreewheelError => write!(f, "freewheel error"),
+            Error::FreewheelTimeout => write!(f, "freewheel render stopped making progress"),
+            Error::TransportNotRolling => write!(f, "transport is not rolling"),
             Error::InvalidDeactivation => write!(f, "invalid deactivation"),
             Error::NotEnoughSpace => write!(f, "not enough space"),
             Error::PortAliasError => write!(f, "port alias error"),
@@ -68,6 +100,8 @@ impl std::fmt::Display for Error {
             Error::PortMonitorError => write!(f, "port monitoring error"),
             Error::PortNamingError => write!(f, "port naming error"),
             Error::PortRegistrationError(p) => write!(f, "failed to register port {p}"),
+            Error::PortTieError => write!(f, "failed to tie ports"),
+            Error::PortUntieError => write!(f, "failed to untie port"),
             Error::SetBufferSizeError => write!(
                 f,
                 "set buffer size error, setting buffer size is likely not supported"
@@ -77,6 +111,31 @@ impl std::fmt::Display for Error {
             Error::ClientIsNoLongerAlive => write!(f, "client is no longer alive"),
             Error::ClientPanicked => write!(f, "client notifcation or processor panicked"),
             Error::RingbufferCreateFailed => write!(f, "ringbuffer creation failed"),
+            Error::ServerCreationError => write!(f, "server creation error"),
+            Error::ServerOpenError => write!(f, "server open error"),
+            Error::ServerStartError => write!(f, "server start error"),
+            Error::ServerStopError => write!(f, "server stop error"),
+            Error::ServerCloseError => write!(f, "server close error"),
+            Error::DriverNotFound(d) => write!(f, "driver {d} not found"),
+            Error::SlaveDriverError(d) => write!(f, "failed to add or remove slave driver {d}"),
+            Error::InternalClientError(c) => {
+                write!(f, "failed to load or unload internal client {c}")
+            }
+            Error::InvalidParameterValue { parameter, reason } => {
+                write!(f, "invalid value for parameter {parameter}: {reason}")
+            }
+            Error::ParameterSetError(p) => write!(f, "failed to set parameter {p}"),
+            Error::TimebaseMasterExists => write!(f, "there is already a timebase master"),
+            Error::NotTimebaseMaster => write!(f, "client is not the timebase master"),
+            Error::InvalidPropertyValue { key, reason } => {
+                write!(f, "invalid value for property {key}: {reason}")
+            }
+            Error::InvalidUuid(s) => write!(f, "{s:?} is not a valid uuid"),
+            Error::ThreadCreationError => write!(f, "failed to create thread"),
+            Error::ThreadJoinError => write!(f, "failed to join thread"),
+            Error::ThreadPanicked => write!(f, "thread panicked"),
+            Error::RealtimeSchedulingError => write!(f, "failed to change real-time scheduling"),
+            Error::InvalidMidiMessage(reason) => write!(f, "invalid midi message: {reason}"),
             Error::UnknownError { error_code } => write!(f, "unkown error with code {error_code}"),
         }
     }
@@ -131,4 +190,11 @@ impl LatencyType {
             LatencyType::Capture => jack_sys::JackCaptureLatency,
         }
     }
+
+    pub fn from_ffi(mode: libc::c_uint) -> Self {
+        match mode {
+            jack_sys::JackPlaybackLatency => LatencyType::Playback,
+            _ => LatencyType::Capture,
+        }
+    }
 }
@@ -25,3 +25,14 @@ pub unsafe fn collect_strs(ptr: *const *const libc::c_char) -> Vec<String> {
     j::jack_free(ptr as *mut ::libc::c_void);
     strs
 }
+
+/// Collects the `data` pointers of a JACK linked list into a Rust vector. The list is owned by
+/// JACK and is not freed. `ptr` may be `null`, in which case an empty vector is returned.
+pub unsafe fn collect_jslist<T>(mut ptr: *const j::JSList) -> Vec<*mut T> {
+    let mut items = Vec::new();
+    while !ptr.is_null() {
+        items.push((*ptr).data as *mut T);
+        ptr = (*ptr).next;
+    }
+    items
+}
@@ -2,9 +2,10 @@
 //!
 //! # Server
 //!
-//! JACK provides a high priority server to manipulate audio and midi across applications. The rust
-//! jack crate does not provide server creation functionality, so a server has to be set up with the
-//! `jackd` commandline tool, `qjackctl` the gui tool, or another method.
+//! JACK provides a high priority server to manipulate audio and midi across applications. A server is
+//! usually set up with the `jackd` commandline tool, `qjackctl` the gui tool, or another method. A
+//! server can also be run within the current process with [`server::Server`], provided the JACK
+//! library exports the `jackctl` API.
 //!
 //! # Client
 //!
@@ -35,15 +36,21 @@
 
 #[allow(deprecated)]
 pub use crate::client::ClosureProcessHandler;
+pub use crate::client::RealtimeJoinHandle;
+#[cfg(not(target_os = "windows"))]
+pub use crate::client::{demote_current_thread, promote_current_thread};
 pub use crate::client::{
     AsyncClient, Client, ClientOptions, ClientStatus, CycleTimes, InternalClientID,
-    NotificationHandler, ProcessHandler, ProcessScope, CLIENT_NAME_SIZE,
+    NotificationHandler, ProcessCycle, ProcessCycles, ProcessHandler, ProcessScope, ProcessThread,
+    ProcessThreadHandler, TimebaseHandler, CLIENT_NAME_SIZE,
 };
+pub use crate::graph::{Connection, Graph, GraphClient, GraphDiff, GraphPort};
 pub use crate::jack_enums::{Control, Error, LatencyType};
 pub use crate::logging::{set_logger, LoggerType};
+pub use crate::metadata::*;
 pub use crate::port::{
-    AudioIn, AudioOut, MidiIn, MidiIter, MidiOut, MidiWriter, Port, PortFlags, PortSpec, RawMidi,
-    Unowned, PORT_NAME_SIZE, PORT_TYPE_SIZE,
+    AudioIn, AudioOut, CvIn, CvOut, MidiIn, MidiIter, MidiMessage, MidiOut, MidiWriter, Port,
+    PortFlags, PortSpec, PortTie, RawMidi, Unowned, PORT_NAME_SIZE, PORT_TYPE_SIZE,
 };
 pub use crate::primitive_types::{Frames, PortId, Time};
 pub use crate::properties::*;
@@ -52,27 +59,49 @@ pub use crate::transport::{
     Transport, TransportBBT, TransportBBTValidationError, TransportPosition, TransportState,
     TransportStatePosition,
 };
+pub use crate::uuid::Uuid;
 
 /// The underlying system bindings for JACK. Can be useful for using possibly experimental stuff
 /// through [`jack_sys::library()`].
 pub use jack_sys;
 
 mod client;
+mod graph;
 mod jack_enums;
 mod jack_utils;
 mod logging;
+mod metadata;
 mod port;
 mod primitive_types;
 mod properties;
 mod ringbuffer;
+pub mod server;
 mod transport;
+mod uuid;
 
 /// A collection of useful but optional functionality.
+///
+/// Methods that handle a single cycle, such as `MidiClockGenerator::process`, are meant to be
+/// called from the process callback and do not allocate or block. If they take a `Client`, it must
+/// be the client that runs the process callback.
 pub mod contrib {
     mod closure;
+    mod lock;
+    mod transport_sync;
 
     pub use closure::ClosureProcessHandler;
 
+    pub mod autoconnect;
+    pub mod freewheel;
+    pub mod graph_tracker;
+    pub mod metadata_cache;
+    pub mod midi_clock;
+    pub mod midi_scheduler;
+    pub mod mtc;
+    pub mod patchbay;
+    pub mod smf;
+    pub mod xrun;
+
     #[cfg(feature = "controller")]
     pub mod controller;
 }
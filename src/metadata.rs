@@ -0,0 +1,445 @@
+//! Typed access to the well-known JACK metadata keys.
+//!
+//! See the JACK Metadata API [documentation](https://jackaudio.org/api/group__Metadata.html) for
+//! the meaning of each key.
+use jack_sys as j;
+use std::fmt;
+
+use crate::properties::{get_property, set_property};
+use crate::{Client, Error, Port, Property, Uuid};
+
+/// A human readable name for a client or port, for use in user interfaces.
+pub const METADATA_PRETTY_NAME: &str = "http://jackaudio.org/metadata/pretty-name";
+
+/// An integer that defines the order of a port among the ports of its client, starting at 0.
+pub const METADATA_ORDER: &str = "http://jackaudio.org/metadata/order";
+
+/// The kind of signal carried by an audio port, see [`SignalType`].
+pub const METADATA_SIGNAL_TYPE: &str = "http://jackaudio.org/metadata/signal-type";
+
+/// A 32x32 PNG icon for a client or port.
+pub const METADATA_ICON_SMALL: &str = "http://jackaudio.org/metadata/icon-small";
+
+/// A 96x96 PNG icon for a client or port.
+pub const METADATA_ICON_LARGE: &str = "http://jackaudio.org/metadata/icon-large";
+
+/// The name of a group of ports that belong together, e.g. the channels of a stereo pair.
+pub const METADATA_PORT_GROUP: &str = "http://jackaudio.org/metadata/port-group";
+
+/// The types of events carried by a port, see [`EventType`].
+pub const METADATA_EVENT_TYPES: &str = "http://jackaudio.org/metadata/event-types";
+
+const INTEGER_TYPE: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const PNG_TYPE: &str = "image/png;base64";
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const ICON_SMALL_SIZE: u32 = 32;
+const ICON_LARGE_SIZE: u32 = 96;
+
+/// The kind of signal carried by an audio port.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SignalType {
+    /// Audio samples.
+    Audio,
+    /// A control voltage, a control signal at audio rate.
+    Cv,
+}
+
+impl SignalType {
+    fn as_str(self) -> &'static str {
+        match self {
+            SignalType::Audio => "AUDIO",
+            SignalType::Cv => "CV",
+        }
+    }
+
+    fn parse(s: &str) -> Option<SignalType> {
+        match s {
+            "AUDIO" => Some(SignalType::Audio),
+            "CV" => Some(SignalType::Cv),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SignalType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A type of event carried by a port.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EventType {
+    /// MIDI messages.
+    Midi,
+    /// Open Sound Control messages.
+    Osc,
+    /// Any other event type.
+    Other(String),
+}
+
+impl EventType {
+    fn as_str(&self) -> &str {
+        match self {
+            EventType::Midi => "MIDI",
+            EventType::Osc => "OSC",
+            EventType::Other(s) => s,
+        }
+    }
+
+    fn parse(s: &str) -> EventType {
+        match s {
+            "MIDI" => EventType::Midi,
+            "OSC" => EventType::Osc,
+            s => EventType::Other(s.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Client {
+    /// Get the pretty name of this client.
+    pub fn pretty_name(&self) -> Option<String> {
+        get_property(self.uuid(), METADATA_PRETTY_NAME).map(|p| p.value().to_string())
+    }
+
+    /// Set the pretty name of this client.
+    pub fn set_pretty_name(&self, name: &str) -> Result<(), Error> {
+        unsafe { set_pretty_name(self.raw(), self.uuid(), name) }
+    }
+
+    /// Get the 32x32 PNG icon of this client.
+    pub fn icon_small(&self) -> Result<Option<Vec<u8>>, Error> {
+        get_icon(self.uuid(), METADATA_ICON_SMALL)
+    }
+
+    /// Set the 32x32 PNG icon of this client. `png` is the content of a PNG file.
+    pub fn set_icon_small(&self, png: &[u8]) -> Result<(), Error> {
+        unsafe { set_icon(self.raw(), self.uuid(), METADATA_ICON_SMALL, png) }
+    }
+
+    /// Get the 96x96 PNG icon of this client.
+    pub fn icon_large(&self) -> Result<Option<Vec<u8>>, Error> {
+        get_icon(self.uuid(), METADATA_ICON_LARGE)
+    }
+
+    /// Set the 96x96 PNG icon of this client. `png` is the content of a PNG file.
+    pub fn set_icon_large(&self, png: &[u8]) -> Result<(), Error> {
+        unsafe { set_icon(self.raw(), self.uuid(), METADATA_ICON_LARGE, png) }
+    }
+}
+
+impl<PS> Port<PS> {
+    /// Get the pretty name of the port.
+    pub fn pretty_name(&self) -> Result<Option<String>, Error> {
+        Ok(get_property(self.uuid()?, METADATA_PRETTY_NAME).map(|p| p.value().to_string()))
+    }
+
+    /// Set the pretty name of the port.
+    pub fn set_pretty_name(&self, name: &str) -> Result<(), Error> {
+        unsafe { set_pretty_name(self.client_ptr(), self.uuid()?, name) }
+    }
+
+    /// Get the order of the port among the ports of its client.
+    pub fn order(&self) -> Result<Option<i32>, Error> {
+        match get_property(self.uuid()?, METADATA_ORDER) {
+            None => Ok(None),
+            Some(p) => match (p.value().parse(), p.typ()) {
+                (Ok(order), None) | (Ok(order), Some(INTEGER_TYPE)) => Ok(Some(order)),
+                _ => Err(invalid(METADATA_ORDER, "expected an integer")),
+            },
+        }
+    }
+
+    /// Set the order of the port among the ports of its client.
+    pub fn set_order(&self, order: i32) -> Result<(), Error> {
+        let property = Property::new(order, Some(INTEGER_TYPE.to_string()));
+        unsafe { set_property(self.client_ptr(), self.uuid()?, METADATA_ORDER, &property) }
+    }
+
+    /// Get the kind of signal carried by the port.
+    pub fn signal_type(&self) -> Result<Option<SignalType>, Error> {
+        match get_property(self.uuid()?, METADATA_SIGNAL_TYPE) {
+            None => Ok(None),
+            Some(p) => SignalType::parse(p.value())
+                .map(Some)
+                .ok_or_else(|| invalid(METADATA_SIGNAL_TYPE, "expected AUDIO or CV")),
+        }
+    }
+
+    /// Set the kind of signal carried by the port.
+    ///
+    /// `Err(Error::InvalidPropertyValue)` is returned if the port is not an audio port.
+    pub fn set_signal_type(&self, signal_type: SignalType) -> Result<(), Error> {
+        if self.port_type()? != j::FLOAT_MONO_AUDIO {
+            return Err(invalid(
+                METADATA_SIGNAL_TYPE,
+                "only audio ports have a signal type",
+            ));
+        }
+        let property = Property::new(signal_type.as_str(), None);
+        unsafe {
+            set_property(
+                self.client_ptr(),
+                self.uuid()?,
+                METADATA_SIGNAL_TYPE,
+                &property,
+            )
+        }
+    }
+
+    /// The signal type of an audio port, where a missing signal type means audio. `None` for
+    /// other ports.
+    pub(crate) fn effective_signal_type(&self) -> Result<Option<SignalType>, Error> {
+        if self.port_type()? != j::FLOAT_MONO_AUDIO {
+            return Ok(None);
+        }
+        Ok(Some(self.signal_type()?.unwrap_or(SignalType::Audio)))
+    }
+
+    /// Get the name of the group the port belongs to.
+    pub fn port_group(&self) -> Result<Option<String>, Error> {
+        Ok(get_property(self.uuid()?, METADATA_PORT_GROUP).map(|p| p.value().to_string()))
+    }
+
+    /// Set the name of the group the port belongs to.
+    pub fn set_port_group(&self, group: &str) -> Result<(), Error> {
+        if group.is_empty() || group.contains('\0') {
+            return Err(invalid(METADATA_PORT_GROUP, "expected a non-empty name"));
+        }
+        let property = Property::new(group, None);
+        unsafe {
+            set_property(
+                self.client_ptr(),
+                self.uuid()?,
+                METADATA_PORT_GROUP,
+                &property,
+            )
+        }
+    }
+
+    /// Get the types of events carried by the port.
+    pub fn event_types(&self) -> Result<Option<Vec<EventType>>, Error> {
+        Ok(get_property(self.uuid()?, METADATA_EVENT_TYPES).map(|p| {
+            p.value()
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(EventType::parse)
+                .collect()
+        }))
+    }
+
+    /// Set the types of events carried by the port.
+    pub fn set_event_types(&self, event_types: &[EventType]) -> Result<(), Error> {
+        let is_valid = |t: &EventType| {
+            let s = t.as_str();
+            !s.is_empty() && s.trim() == s && !s.contains([',', '\0'])
+        };
+        if event_types.is_empty() || !event_types.iter().all(is_valid) {
+            return Err(invalid(
+                METADATA_EVENT_TYPES,
+                "expected non-empty event types without commas",
+            ));
+        }
+        let value: Vec<&str> = event_types.iter().map(EventType::as_str).collect();
+        let property = Property::new(value.join(","), None);
+        unsafe {
+            set_property(
+                self.client_ptr(),
+                self.uuid()?,
+                METADATA_EVENT_TYPES,
+                &property,
+            )
+        }
+    }
+
+    /// Get the 32x32 PNG icon of the port.
+    pub fn icon_small(&self) -> Result<Option<Vec<u8>>, Error> {
+        get_icon(self.uuid()?, METADATA_ICON_SMALL)
+    }
+
+    /// Set the 32x32 PNG icon of the port. `png` is the content of a PNG file.
+    pub fn set_icon_small(&self, png: &[u8]) -> Result<(), Error> {
+        unsafe { set_icon(self.client_ptr(), self.uuid()?, METADATA_ICON_SMALL, png) }
+    }
+
+    /// Get the 96x96 PNG icon of the port.
+    pub fn icon_large(&self) -> Result<Option<Vec<u8>>, Error> {
+        get_icon(self.uuid()?, METADATA_ICON_LARGE)
+    }
+
+    /// Set the 96x96 PNG icon of the port. `png` is the content of a PNG file.
+    pub fn set_icon_large(&self, png: &[u8]) -> Result<(), Error> {
+        unsafe { set_icon(self.client_ptr(), self.uuid()?, METADATA_ICON_LARGE, png) }
+    }
+}
+
+fn invalid(key: &str, reason: &'static str) -> Error {
+    Error::InvalidPropertyValue {
+        key: key.to_string(),
+        reason,
+    }
+}
+
+unsafe fn set_pretty_name(
+    client: *mut j::jack_client_t,
+    subject: Uuid,
+    name: &str,
+) -> Result<(), Error> {
+    if name.is_empty() || name.contains('\0') {
+        return Err(invalid(METADATA_PRETTY_NAME, "expected a non-empty name"));
+    }
+    set_property(
+        client,
+        subject,
+        METADATA_PRETTY_NAME,
+        &Property::new(name, None),
+    )
+}
+
+fn get_icon(subject: Uuid, key: &str) -> Result<Option<Vec<u8>>, Error> {
+    let property = match get_property(subject, key) {
+        None => return Ok(None),
+        Some(p) => p,
+    };
+    if property.typ() != Some(PNG_TYPE) {
+        return Err(invalid(key, "expected type image/png;base64"));
+    }
+    base64_decode(property.value())
+        .map(Some)
+        .ok_or_else(|| invalid(key, "expected base64 encoded data"))
+}
+
+unsafe fn set_icon(
+    client: *mut j::jack_client_t,
+    subject: Uuid,
+    key: &str,
+    png: &[u8],
+) -> Result<(), Error> {
+    let size = if key == METADATA_ICON_SMALL {
+        ICON_SMALL_SIZE
+    } else {
+        ICON_LARGE_SIZE
+    };
+    if png_dimensions(png) != Some((size, size)) {
+        return Err(invalid(
+            key,
+            if size == ICON_SMALL_SIZE {
+                "expected a 32x32 PNG image"
+            } else {
+                "expected a 96x96 PNG image"
+            },
+        ));
+    }
+    let property = Property::new(base64_encode(png), Some(PNG_TYPE.to_string()));
+    set_property(client, subject, key, &property)
+}
+
+// Returns the width and height from the header of a PNG file.
+fn png_dimensions(png: &[u8]) -> Option<(u32, u32)> {
+    if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+    Some((width, height))
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for &c in s {
+        let v = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        acc = (acc << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&width.to_be_bytes());
+        png.extend_from_slice(&height.to_be_bytes());
+        png
+    }
+
+    #[test]
+    fn base64_roundtrips() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_decode("Zg=="), Some(b"f".to_vec()));
+    }
+
+    #[test]
+    fn base64_rejects_invalid_characters() {
+        assert_eq!(base64_decode("Zm9v!"), None);
+    }
+
+    #[test]
+    fn png_dimensions_are_read_from_header() {
+        assert_eq!(png_dimensions(&png_header(32, 32)), Some((32, 32)));
+        assert_eq!(png_dimensions(&png_header(96, 48)), Some((96, 48)));
+        assert_eq!(png_dimensions(b"not a png file at all..."), None);
+        assert_eq!(png_dimensions(&png_header(32, 32)[..20]), None);
+    }
+
+    #[test]
+    fn signal_and_event_types_roundtrip() {
+        for t in [SignalType::Audio, SignalType::Cv] {
+            assert_eq!(SignalType::parse(t.as_str()), Some(t));
+        }
+        assert_eq!(SignalType::parse("audio"), None);
+        for t in [
+            EventType::Midi,
+            EventType::Osc,
+            EventType::Other("SYSEX".to_string()),
+        ] {
+            assert_eq!(EventType::parse(t.as_str()), t);
+        }
+    }
+}
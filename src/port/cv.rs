@@ -0,0 +1,112 @@
+use jack_sys as j;
+use std::slice;
+
+use crate::{Port, PortFlags, PortSpec, ProcessScope, SignalType};
+
+/// [`CvIn`] implements the [`PortSpec`] trait, which defines an
+/// endpoint for JACK. In this case, it is a readable 32 bit floating
+/// point buffer for control voltage.
+///
+/// CV ports are audio ports with the `signal-type` metadata set to `CV`,
+/// which is done when the port is registered.
+///
+/// [`Port::as_slice()`] is used to gain access the buffer.
+///
+/// # Example
+/// ```
+/// let client = jack::Client::new("rusty_client", jack::ClientOptions::default())
+///     .unwrap()
+///     .0;
+/// let spec = jack::CvIn::default();
+/// let cv_in_port = client.register_port("cv_in", spec).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CvIn {
+    _internal: (),
+}
+
+/// [`CvOut`] implements the [`PortSpec`] trait, which defines an
+/// endpoint for JACK. In this case, it is a mutable 32 bit floating
+/// point buffer for control voltage.
+///
+/// CV ports are audio ports with the `signal-type` metadata set to `CV`,
+/// which is done when the port is registered.
+///
+/// [`Port::as_mut_slice()`] is used to gain access the buffer.
+///
+/// # Example
+/// ```
+/// let client = jack::Client::new("rusty_client", jack::ClientOptions::default())
+///     .unwrap()
+///     .0;
+/// let spec = jack::CvOut::default();
+/// let cv_out_port = client.register_port("cv_out", spec).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CvOut {
+    _internal: (),
+}
+
+unsafe impl PortSpec for CvOut {
+    fn jack_port_type(&self) -> &'static str {
+        j::FLOAT_MONO_AUDIO
+    }
+
+    fn jack_flags(&self) -> PortFlags {
+        PortFlags::IS_OUTPUT
+    }
+
+    fn jack_buffer_size(&self) -> libc::c_ulong {
+        // Not needed for built in types according to JACK api
+        0
+    }
+
+    fn jack_signal_type(&self) -> Option<SignalType> {
+        Some(SignalType::Cv)
+    }
+}
+
+unsafe impl PortSpec for CvIn {
+    fn jack_port_type(&self) -> &'static str {
+        j::FLOAT_MONO_AUDIO
+    }
+
+    fn jack_flags(&self) -> PortFlags {
+        PortFlags::IS_INPUT
+    }
+
+    fn jack_buffer_size(&self) -> libc::c_ulong {
+        // Not needed for built in types according to JACK api
+        0
+    }
+
+    fn jack_signal_type(&self) -> Option<SignalType> {
+        Some(SignalType::Cv)
+    }
+}
+
+impl Port<CvIn> {
+    /// Read the received control voltage data.
+    pub fn as_slice<'a>(&'a self, ps: &'a ProcessScope) -> &'a [f32] {
+        assert_eq!(self.client_ptr(), ps.client_ptr());
+        unsafe {
+            slice::from_raw_parts(
+                self.buffer(ps.n_frames()) as *const f32,
+                ps.n_frames() as usize,
+            )
+        }
+    }
+}
+
+impl Port<CvOut> {
+    /// Get a slice to write control voltage data to.
+    pub fn as_mut_slice<'a>(&'a mut self, ps: &'a ProcessScope) -> &'a mut [f32] {
+        assert_eq!(self.client_ptr(), ps.client_ptr());
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.buffer(ps.n_frames()) as *mut f32,
+                ps.n_frames() as usize,
+            )
+        }
+    }
+}
@@ -2,7 +2,7 @@ use jack_sys as j;
 use std::marker::PhantomData;
 use std::{mem, slice};
 
-use crate::{Error, Frames, Port, PortFlags, PortSpec, ProcessScope};
+use crate::{Error, Frames, MidiMessage, Port, PortFlags, PortSpec, ProcessScope};
 
 /// Contains 8bit raw midi information along with a timestamp relative to the
 /// process cycle.
@@ -195,6 +195,42 @@ impl MidiWriter<'_> {
         }
     }
 
+    /// Reserve space for an event of `size` bytes at `time` and return it for writing the event in
+    /// place.
+    ///
+    /// This avoids copying large events, such as system exclusive messages, from a staging buffer.
+    /// The reserved bytes have unspecified contents and must all be written with normalised midi
+    /// data, see [`MidiWriter::write`]. The writer is borrowed until the returned slice is dropped,
+    /// so reservations can not overlap:
+    ///
+    /// ```compile_fail
+    /// fn reserve_twice(writer: &mut jack::MidiWriter) {
+    ///     let first = writer.reserve(0, 3).unwrap();
+    ///     let second = writer.reserve(1, 3).unwrap();
+    ///     first.copy_from_slice(&[0x90, 60, 100]);
+    /// }
+    /// ```
+    ///
+    /// `Err(Error::NotEnoughSpace)` is returned if the event does not fit into the buffer or if
+    /// `time` is earlier than the time of the previously written event.
+    pub fn reserve(&mut self, time: Frames, size: usize) -> Result<&mut [u8], Error> {
+        let ptr = unsafe { j::jack_midi_event_reserve(self.buffer, time, size) };
+        if ptr.is_null() {
+            return Err(Error::NotEnoughSpace);
+        }
+        Ok(unsafe { slice::from_raw_parts_mut(ptr, size) })
+    }
+
+    /// Encode `message` and write it into the port buffer at `time`, relative to the start of the
+    /// process cycle.
+    ///
+    /// Like [`MidiWriter::write`], events must be written in order of their time.
+    pub fn write_message(&mut self, time: Frames, message: &MidiMessage) -> Result<(), Error> {
+        let mut buffer = [0; MidiMessage::MAX_SHORT_MESSAGE_SIZE];
+        let bytes = message.encode(&mut buffer)?;
+        self.write(&RawMidi { time, bytes })
+    }
+
     /// Get the number of events that could not be written to port_buffer.
     ///
     /// If the return value is greater than 0, than the buffer is full.  Currently, the only way
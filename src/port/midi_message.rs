@@ -0,0 +1,394 @@
+use std::convert::TryFrom;
+
+use crate::{Error, RawMidi};
+
+/// A decoded midi message.
+///
+/// Decoding and encoding do not allocate, so both can be used in the process callback. Channels
+/// are in the range `0..16` and all other values are in the range of their midi data bytes.
+///
+/// # Example
+/// ```
+/// use std::convert::TryFrom;
+///
+/// let raw = jack::RawMidi {
+///     time: 0,
+///     bytes: &[0x91, 60, 100],
+/// };
+/// let message = jack::MidiMessage::try_from(raw).unwrap();
+/// assert_eq!(
+///     message,
+///     jack::MidiMessage::NoteOn {
+///         channel: 1,
+///         note: 60,
+///         velocity: 100
+///     }
+/// );
+///
+/// let mut buffer = [0; 3];
+/// assert_eq!(message.encode(&mut buffer).unwrap(), raw.bytes);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MidiMessage<'a> {
+    /// A key was released.
+    NoteOff { channel: u8, note: u8, velocity: u8 },
+    /// A key was pressed. Many devices send a `NoteOn` with a velocity of `0` instead of a
+    /// `NoteOff`, this is not converted.
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// The pressure on a single key changed, also known as polyphonic aftertouch.
+    PolyPressure { channel: u8, note: u8, pressure: u8 },
+    /// A controller changed its value.
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A program was selected.
+    ProgramChange { channel: u8, program: u8 },
+    /// The pressure on all keys of a channel changed, also known as channel aftertouch.
+    ChannelPressure { channel: u8, pressure: u8 },
+    /// The pitch wheel moved. `value` is in the range `0..16384`, with `8192` meaning no bend.
+    PitchBend { channel: u8, value: u16 },
+    /// A system exclusive message, including the leading `0xF0` and trailing `0xF7` bytes.
+    SysEx(&'a [u8]),
+    /// A midi time code quarter frame. `piece` is in the range `0..8` and `value` in the range
+    /// `0..16`.
+    MtcQuarterFrame { piece: u8, value: u8 },
+    /// The song position in midi beats, where one midi beat is 6 midi clocks.
+    SongPosition(u16),
+    /// A midi clock, sent 24 times per quarter note.
+    Clock,
+    /// Start playback from the beginning of the song.
+    Start,
+    /// Continue playback from the current song position.
+    Continue,
+    /// Stop playback.
+    Stop,
+}
+
+impl<'a> MidiMessage<'a> {
+    /// The largest size of an encoded message, except for [`MidiMessage::SysEx`].
+    pub const MAX_SHORT_MESSAGE_SIZE: usize = 3;
+
+    /// Decode a single, complete midi message.
+    ///
+    /// Running status and status bytes of messages that are not represented by `MidiMessage` are
+    /// rejected with `Err(Error::InvalidMidiMessage)`.
+    pub fn parse(bytes: &'a [u8]) -> Result<MidiMessage<'a>, Error> {
+        let (&status, data) = bytes
+            .split_first()
+            .ok_or(Error::InvalidMidiMessage("empty message"))?;
+        if status < 0x80 {
+            return Err(Error::InvalidMidiMessage("missing status byte"));
+        }
+        if status == 0xF0 {
+            return parse_sysex(bytes);
+        }
+        if data.iter().any(|&b| b >= 0x80) {
+            return Err(Error::InvalidMidiMessage("data byte out of range"));
+        }
+        let channel = status & 0x0F;
+        let message = match (status & 0xF0, data) {
+            (0x80, &[note, velocity]) => MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            },
+            (0x90, &[note, velocity]) => MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            },
+            (0xA0, &[note, pressure]) => MidiMessage::PolyPressure {
+                channel,
+                note,
+                pressure,
+            },
+            (0xB0, &[controller, value]) => MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            },
+            (0xC0, &[program]) => MidiMessage::ProgramChange { channel, program },
+            (0xD0, &[pressure]) => MidiMessage::ChannelPressure { channel, pressure },
+            (0xE0, &[lsb, msb]) => MidiMessage::PitchBend {
+                channel,
+                value: join_14_bits(lsb, msb),
+            },
+            (0xF0, _) => match (status, data) {
+                (0xF1, &[b]) => MidiMessage::MtcQuarterFrame {
+                    piece: b >> 4,
+                    value: b & 0x0F,
+                },
+                (0xF2, &[lsb, msb]) => MidiMessage::SongPosition(join_14_bits(lsb, msb)),
+                (0xF8, &[]) => MidiMessage::Clock,
+                (0xFA, &[]) => MidiMessage::Start,
+                (0xFB, &[]) => MidiMessage::Continue,
+                (0xFC, &[]) => MidiMessage::Stop,
+                (0xF1, _) | (0xF2, _) | (0xF8, _) | (0xFA..=0xFC, _) => {
+                    return Err(Error::InvalidMidiMessage("wrong message length"))
+                }
+                _ => return Err(Error::InvalidMidiMessage("unsupported status byte")),
+            },
+            _ => return Err(Error::InvalidMidiMessage("wrong message length")),
+        };
+        Ok(message)
+    }
+
+    /// Encode the message into midi bytes.
+    ///
+    /// Short messages are written to the start of `buffer` and a slice of it is returned. A
+    /// [`MidiMessage::SysEx`] returns its own bytes without touching `buffer`.
+    /// `Err(Error::InvalidMidiMessage)` is returned if a field is out of range.
+    pub fn encode<'b>(
+        &'b self,
+        buffer: &'b mut [u8; MidiMessage::MAX_SHORT_MESSAGE_SIZE],
+    ) -> Result<&'b [u8], Error> {
+        let len = match *self {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => encode_channel(buffer, 0x80, channel, &[note, velocity])?,
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => encode_channel(buffer, 0x90, channel, &[note, velocity])?,
+            MidiMessage::PolyPressure {
+                channel,
+                note,
+                pressure,
+            } => encode_channel(buffer, 0xA0, channel, &[note, pressure])?,
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => encode_channel(buffer, 0xB0, channel, &[controller, value])?,
+            MidiMessage::ProgramChange { channel, program } => {
+                encode_channel(buffer, 0xC0, channel, &[program])?
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                encode_channel(buffer, 0xD0, channel, &[pressure])?
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                encode_channel(buffer, 0xE0, channel, &split_14_bits(value)?)?
+            }
+            MidiMessage::SysEx(bytes) => {
+                parse_sysex(bytes)?;
+                return Ok(bytes);
+            }
+            MidiMessage::MtcQuarterFrame { piece, value } => {
+                if piece >= 8 || value >= 16 {
+                    return Err(Error::InvalidMidiMessage("quarter frame out of range"));
+                }
+                encode_system(buffer, 0xF1, &[piece << 4 | value])
+            }
+            MidiMessage::SongPosition(beats) => encode_system(buffer, 0xF2, &split_14_bits(beats)?),
+            MidiMessage::Clock => encode_system(buffer, 0xF8, &[]),
+            MidiMessage::Start => encode_system(buffer, 0xFA, &[]),
+            MidiMessage::Continue => encode_system(buffer, 0xFB, &[]),
+            MidiMessage::Stop => encode_system(buffer, 0xFC, &[]),
+        };
+        Ok(&buffer[..len])
+    }
+}
+
+impl<'a> TryFrom<RawMidi<'a>> for MidiMessage<'a> {
+    type Error = Error;
+
+    fn try_from(raw: RawMidi<'a>) -> Result<MidiMessage<'a>, Error> {
+        MidiMessage::parse(raw.bytes)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for MidiMessage<'a> {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<MidiMessage<'a>, Error> {
+        MidiMessage::parse(bytes)
+    }
+}
+
+fn parse_sysex(bytes: &[u8]) -> Result<MidiMessage<'_>, Error> {
+    match bytes {
+        [0xF0, data @ .., 0xF7] if data.iter().all(|&b| b < 0x80) => Ok(MidiMessage::SysEx(bytes)),
+        _ => Err(Error::InvalidMidiMessage(
+            "malformed system exclusive message",
+        )),
+    }
+}
+
+fn join_14_bits(lsb: u8, msb: u8) -> u16 {
+    u16::from(msb) << 7 | u16::from(lsb)
+}
+
+fn split_14_bits(value: u16) -> Result<[u8; 2], Error> {
+    if value >= 1 << 14 {
+        return Err(Error::InvalidMidiMessage("14 bit value out of range"));
+    }
+    Ok([(value & 0x7F) as u8, (value >> 7) as u8])
+}
+
+fn encode_channel(
+    buffer: &mut [u8; MidiMessage::MAX_SHORT_MESSAGE_SIZE],
+    status: u8,
+    channel: u8,
+    data: &[u8],
+) -> Result<usize, Error> {
+    if channel >= 16 {
+        return Err(Error::InvalidMidiMessage("channel out of range"));
+    }
+    if data.iter().any(|&b| b >= 0x80) {
+        return Err(Error::InvalidMidiMessage("data byte out of range"));
+    }
+    Ok(encode_system(buffer, status | channel, data))
+}
+
+fn encode_system(
+    buffer: &mut [u8; MidiMessage::MAX_SHORT_MESSAGE_SIZE],
+    status: u8,
+    data: &[u8],
+) -> usize {
+    buffer[0] = status;
+    buffer[1..=data.len()].copy_from_slice(data);
+    data.len() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(bytes: &[u8]) -> MidiMessage<'_> {
+        let message = MidiMessage::parse(bytes).unwrap();
+        let mut buffer = [0; MidiMessage::MAX_SHORT_MESSAGE_SIZE];
+        assert_eq!(message.encode(&mut buffer).unwrap(), bytes);
+        message
+    }
+
+    #[test]
+    fn channel_messages_roundtrip() {
+        assert_eq!(
+            roundtrip(&[0x80, 60, 0]),
+            MidiMessage::NoteOff {
+                channel: 0,
+                note: 60,
+                velocity: 0
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0x9F, 61, 127]),
+            MidiMessage::NoteOn {
+                channel: 15,
+                note: 61,
+                velocity: 127
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0xA2, 62, 10]),
+            MidiMessage::PolyPressure {
+                channel: 2,
+                note: 62,
+                pressure: 10
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0xB3, 7, 100]),
+            MidiMessage::ControlChange {
+                channel: 3,
+                controller: 7,
+                value: 100
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0xC4, 5]),
+            MidiMessage::ProgramChange {
+                channel: 4,
+                program: 5
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0xD5, 90]),
+            MidiMessage::ChannelPressure {
+                channel: 5,
+                pressure: 90
+            }
+        );
+        assert_eq!(
+            roundtrip(&[0xE6, 0x00, 0x40]),
+            MidiMessage::PitchBend {
+                channel: 6,
+                value: 8192
+            }
+        );
+    }
+
+    #[test]
+    fn system_messages_roundtrip() {
+        assert_eq!(
+            roundtrip(&[0xF0, 0x7E, 0x01, 0xF7]),
+            MidiMessage::SysEx(&[0xF0, 0x7E, 0x01, 0xF7])
+        );
+        assert_eq!(
+            roundtrip(&[0xF1, 0x35]),
+            MidiMessage::MtcQuarterFrame { piece: 3, value: 5 }
+        );
+        assert_eq!(
+            roundtrip(&[0xF2, 0x7F, 0x7F]),
+            MidiMessage::SongPosition(16383)
+        );
+        assert_eq!(roundtrip(&[0xF8]), MidiMessage::Clock);
+        assert_eq!(roundtrip(&[0xFA]), MidiMessage::Start);
+        assert_eq!(roundtrip(&[0xFB]), MidiMessage::Continue);
+        assert_eq!(roundtrip(&[0xFC]), MidiMessage::Stop);
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        for bytes in [
+            &[][..],
+            &[60, 100],
+            &[0x90, 60],
+            &[0x90, 60, 100, 1],
+            &[0x90, 0x80, 100],
+            &[0xF8, 0],
+            &[0xFE],
+            &[0xF0, 0x01],
+            &[0xF0, 0x80, 0xF7],
+        ] {
+            assert!(
+                matches!(MidiMessage::parse(bytes), Err(Error::InvalidMidiMessage(_))),
+                "{:?} should not parse",
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_fields_are_not_encoded() {
+        let mut buffer = [0; MidiMessage::MAX_SHORT_MESSAGE_SIZE];
+        for message in [
+            MidiMessage::NoteOn {
+                channel: 16,
+                note: 60,
+                velocity: 100,
+            },
+            MidiMessage::ControlChange {
+                channel: 0,
+                controller: 128,
+                value: 0,
+            },
+            MidiMessage::PitchBend {
+                channel: 0,
+                value: 16384,
+            },
+            MidiMessage::MtcQuarterFrame { piece: 8, value: 0 },
+            MidiMessage::SysEx(&[0xF0, 0x01]),
+        ] {
+            assert!(matches!(
+                message.encode(&mut buffer),
+                Err(Error::InvalidMidiMessage(_))
+            ));
+        }
+    }
+}
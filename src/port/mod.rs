@@ -1,11 +1,15 @@
 mod audio;
+mod cv;
 mod midi;
+mod midi_message;
 mod port_impl;
 
 /// Contains flag constants that may be used to create [`PortFlags`].
 mod port_flags;
 
 pub use self::audio::{AudioIn, AudioOut};
+pub use self::cv::{CvIn, CvOut};
 pub use self::midi::{MidiIn, MidiIter, MidiOut, MidiWriter, RawMidi};
+pub use self::midi_message::MidiMessage;
 pub use self::port_flags::PortFlags;
-pub use self::port_impl::{Port, PortSpec, Unowned, PORT_NAME_SIZE, PORT_TYPE_SIZE};
+pub use self::port_impl::{Port, PortSpec, PortTie, Unowned, PORT_NAME_SIZE, PORT_TYPE_SIZE};
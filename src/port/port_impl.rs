@@ -7,7 +7,7 @@ use std::marker::Sized;
 use std::sync::Weak;
 use std::{ffi, fmt, iter};
 
-use crate::{Error, Frames, LatencyType, PortFlags};
+use crate::{AudioIn, AudioOut, Error, Frames, LatencyType, PortFlags, Uuid};
 
 lazy_static! {
     /// The maximum string length for port names.
@@ -32,6 +32,12 @@ pub unsafe trait PortSpec: Sized {
 
     /// Size used by jack upon port creation.
     fn jack_buffer_size(&self) -> libc::c_ulong;
+
+    /// The signal type that is set as metadata right after port creation. Only audio ports have a
+    /// signal type, see [`SignalType`](crate::SignalType).
+    fn jack_signal_type(&self) -> Option<crate::SignalType> {
+        None
+    }
 }
 
 /// An endpoint to interact with JACK data streams, for audio, midi, etc...
@@ -71,6 +77,13 @@ impl<PS> Port<PS> {
         }
     }
 
+    /// Returns the `Uuid` of the port. It can be used as the subject of metadata, see
+    /// [`Client::property_set`](crate::Client::property_set).
+    pub fn uuid(&self) -> Result<Uuid, Error> {
+        self.check_client_life()?;
+        Ok(Uuid::from_raw(unsafe { j::jack_port_uuid(self.raw()) }))
+    }
+
     /// Returns the full name of the port, including the "client_name:" prefix.
     pub fn name(&self) -> Result<String, Error> {
         self.check_client_life()?;
@@ -330,7 +343,7 @@ impl<PS> Port<PS> {
 
     /// Returns the UUIDs of all clients currently connected to this one
     /// Remarks: Not realtime safe
-    pub fn get_connected_client_uuids(&self) -> Vec<jack_sys::jack_uuid_t> {
+    pub fn get_connected_client_uuids(&self) -> Vec<Uuid> {
         self.get_connections()
             .into_iter()
             .map(|name| {
@@ -341,6 +354,46 @@ impl<PS> Port<PS> {
     }
 }
 
+/// A tie between an input and an output port of the same client, created by
+/// [`Client::tie_ports`](crate::Client::tie_ports).
+///
+/// The tie borrows both ports, so they can not be unregistered while they are tied. It lasts until
+/// [`PortTie::untie`] is called. Dropping the `PortTie` does not untie the ports.
+#[must_use = "The ports stay tied until `PortTie::untie` is called."]
+#[derive(Debug)]
+pub struct PortTie<'a> {
+    input: &'a Port<AudioIn>,
+    output: &'a Port<AudioOut>,
+}
+
+impl<'a> PortTie<'a> {
+    /// Create the `PortTie` of a successful call to `jack_port_tie`.
+    pub(crate) fn new(input: &'a Port<AudioIn>, output: &'a Port<AudioOut>) -> Self {
+        PortTie { input, output }
+    }
+
+    /// The tied input port.
+    pub fn input(&self) -> &'a Port<AudioIn> {
+        self.input
+    }
+
+    /// The tied output port.
+    pub fn output(&self) -> &'a Port<AudioOut> {
+        self.output
+    }
+
+    /// Undo the tie so that the ports are processed independently again.
+    ///
+    /// `Err(Error::PortUntieError)` is returned on failure.
+    pub fn untie(self) -> Result<(), Error> {
+        self.output.check_client_life()?;
+        match unsafe { j::jack_port_untie(self.output.raw()) } {
+            0 => Ok(()),
+            _ => Err(Error::PortUntieError),
+        }
+    }
+}
+
 /// `PortSpec` for a port that holds has no readable or writeable data from JACK on the created
 /// client. It can be used to connect ports or to obtain metadata.
 #[derive(Debug, Default)]
@@ -2,16 +2,17 @@
 //!
 use std::panic::catch_unwind;
 
-use j::jack_uuid_t as uuid;
 use jack_sys as j;
 
+use crate::Uuid;
+
 /// A description of a Metadata change describint a creation, change or deletion, its owner
 /// `subject` and `key`.
 #[derive(Debug, PartialEq, Eq)]
 pub enum PropertyChange<'a> {
-    Created { subject: uuid, key: &'a str },
-    Changed { subject: uuid, key: &'a str },
-    Deleted { subject: uuid, key: &'a str },
+    Created { subject: Uuid, key: &'a str },
+    Changed { subject: Uuid, key: &'a str },
+    Deleted { subject: Uuid, key: &'a str },
 }
 
 /// A trait for reacting to property changes.
@@ -35,6 +36,7 @@ pub(crate) unsafe extern "C" fn property_changed<P>(
         let h: &mut P = &mut *(arg as *mut P);
         let key_c = std::ffi::CStr::from_ptr(key);
         let key = key_c.to_str().expect("to convert key to valid str");
+        let subject = Uuid::from_raw(subject);
         let c = match change {
             j::PropertyCreated => PropertyChange::Created { subject, key },
             j::PropertyDeleted => PropertyChange::Deleted { subject, key },
@@ -51,7 +53,7 @@ pub(crate) unsafe extern "C" fn property_changed<P>(
 pub use metadata::*;
 
 mod metadata {
-    use super::{j, uuid, PropertyChange, PropertyChangeHandler};
+    use super::{j, PropertyChange, PropertyChangeHandler, Uuid};
     use crate::Error;
     use std::{
         collections::HashMap,
@@ -65,9 +67,9 @@ mod metadata {
     /// A helper enum, allowing for sending changes between threads.
     #[derive(Debug, Clone, PartialEq, Eq)]
     pub enum PropertyChangeOwned {
-        Created { subject: uuid, key: String },
-        Changed { subject: uuid, key: String },
-        Deleted { subject: uuid, key: String },
+        Created { subject: Uuid, key: String },
+        Changed { subject: Uuid, key: String },
+        Deleted { subject: Uuid, key: String },
     }
 
     /// A piece of Metadata on a Jack `subject`: either a port or a client.
@@ -119,6 +121,83 @@ mod metadata {
         }
     }
 
+    // Helper to get a property without a client.
+    pub(crate) fn get_property(subject: Uuid, key: &str) -> Option<Property> {
+        let key = ffi::CString::new(key).expect("key to be convert to CString");
+        let mut value: MaybeUninit<*mut ::libc::c_char> = MaybeUninit::uninit();
+        let mut typ: MaybeUninit<*mut ::libc::c_char> = MaybeUninit::uninit();
+
+        unsafe {
+            if j::jack_get_property(
+                subject.raw(),
+                key.as_ptr(),
+                value.as_mut_ptr(),
+                typ.as_mut_ptr(),
+            ) == 0
+            {
+                let value = value.assume_init();
+                let typ = typ.assume_init();
+                let r = Some(Property::new(
+                    ffi::CStr::from_ptr(value).to_str().unwrap(),
+                    if typ.is_null() {
+                        None
+                    } else {
+                        Some(ffi::CStr::from_ptr(typ).to_str().unwrap().to_string())
+                    },
+                ));
+                j::jack_free(value as _);
+                if !typ.is_null() {
+                    j::jack_free(typ as _)
+                }
+                r
+            } else {
+                None
+            }
+        }
+    }
+
+    // Helper to set a property with a raw client pointer.
+    pub(crate) unsafe fn set_property(
+        client: *mut j::jack_client_t,
+        subject: Uuid,
+        key: &str,
+        property: &Property,
+    ) -> Result<(), Error> {
+        let key = ffi::CString::new(key).expect("to create cstring from key");
+        let value =
+            ffi::CString::new(property.value.as_str()).expect("to create cstring from value");
+        map_error(|| {
+            if let Some(t) = property.typ() {
+                let t = ffi::CString::new(t).unwrap();
+                j::jack_set_property(
+                    client,
+                    subject.raw(),
+                    key.as_ptr(),
+                    value.as_ptr(),
+                    t.as_ptr(),
+                )
+            } else {
+                j::jack_set_property(
+                    client,
+                    subject.raw(),
+                    key.as_ptr(),
+                    value.as_ptr(),
+                    ptr::null(),
+                )
+            }
+        })
+    }
+
+    // Helper to remove a property with a raw client pointer.
+    pub(crate) unsafe fn remove_property(
+        client: *mut j::jack_client_t,
+        subject: Uuid,
+        key: &str,
+    ) -> Result<(), Error> {
+        let key = ffi::CString::new(key).expect("to create cstring from key");
+        map_error(|| j::jack_remove_property(client, subject.raw(), key.as_ptr()))
+    }
+
     //helper to convert to an Option<PropertyMap> and free
     unsafe fn description_to_map_free(
         description: *mut j::jack_description_t,
@@ -193,34 +272,8 @@ mod metadata {
         ///
         /// * `subject` - The subject of the property.
         /// * `key` - The key of the property, a URI String.
-        pub fn property_get(&self, subject: uuid, key: &str) -> Option<Property> {
-            let key = ffi::CString::new(key).expect("key to be convert to CString");
-            let mut value: MaybeUninit<*mut ::libc::c_char> = MaybeUninit::uninit();
-            let mut typ: MaybeUninit<*mut ::libc::c_char> = MaybeUninit::uninit();
-
-            unsafe {
-                if j::jack_get_property(subject, key.as_ptr(), value.as_mut_ptr(), typ.as_mut_ptr())
-                    == 0
-                {
-                    let value = value.assume_init();
-                    let typ = typ.assume_init();
-                    let r = Some(Property::new(
-                        ffi::CStr::from_ptr(value).to_str().unwrap(),
-                        if typ.is_null() {
-                            None
-                        } else {
-                            Some(ffi::CStr::from_ptr(typ).to_str().unwrap().to_string())
-                        },
-                    ));
-                    j::jack_free(value as _);
-                    if !typ.is_null() {
-                        j::jack_free(typ as _)
-                    }
-                    r
-                } else {
-                    None
-                }
-            }
+        pub fn property_get(&self, subject: Uuid, key: &str) -> Option<Property> {
+            get_property(subject, key)
         }
 
         /// Get all the properties from a subject.
@@ -232,10 +285,10 @@ mod metadata {
         /// # Remarks
         ///
         /// * The Jack API calls this data a 'description'.
-        pub fn property_get_subject(&self, subject: uuid) -> Option<PropertyMap> {
+        pub fn property_get_subject(&self, subject: Uuid) -> Option<PropertyMap> {
             let mut description: MaybeUninit<j::jack_description_t> = MaybeUninit::uninit();
             unsafe {
-                let _ = j::jack_get_properties(subject, description.as_mut_ptr());
+                let _ = j::jack_get_properties(subject.raw(), description.as_mut_ptr());
                 description_to_map_free(description.as_mut_ptr())
             }
         }
@@ -245,7 +298,7 @@ mod metadata {
         /// # Remarks
         ///
         /// * The Jack API calls these maps 'descriptions'.
-        pub fn property_get_all(&self) -> HashMap<uuid, PropertyMap> {
+        pub fn property_get_all(&self) -> HashMap<Uuid, PropertyMap> {
             let mut map = HashMap::new();
             let mut descriptions: MaybeUninit<*mut j::jack_description_t> = MaybeUninit::uninit();
             unsafe {
@@ -253,7 +306,7 @@ mod metadata {
                 if cnt > 0 {
                     let descriptions = descriptions.assume_init();
                     for des in std::slice::from_raw_parts_mut(descriptions, cnt as usize) {
-                        let uuid = (des).subject;
+                        let uuid = Uuid::from_raw((des).subject);
                         if let Some(dmap) = description_to_map_free(des) {
                             map.insert(uuid, dmap);
                         }
@@ -272,33 +325,11 @@ mod metadata {
         /// * `key` - The key of the property. A URI string.
         pub fn property_set(
             &self,
-            subject: uuid,
+            subject: Uuid,
             key: &str,
             property: &Property,
         ) -> Result<(), Error> {
-            let key = ffi::CString::new(key).expect("to create cstring from key");
-            let value =
-                ffi::CString::new(property.value.as_str()).expect("to create cstring from value");
-            map_error(|| unsafe {
-                if let Some(t) = property.typ() {
-                    let t = ffi::CString::new(t).unwrap();
-                    j::jack_set_property(
-                        self.raw(),
-                        subject,
-                        key.as_ptr(),
-                        value.as_ptr(),
-                        t.as_ptr(),
-                    )
-                } else {
-                    j::jack_set_property(
-                        self.raw(),
-                        subject,
-                        key.as_ptr(),
-                        value.as_ptr(),
-                        ptr::null(),
-                    )
-                }
-            })
+            unsafe { set_property(self.raw(), subject, key, property) }
         }
 
         /// Remove a single property from a subject.
@@ -307,9 +338,8 @@ mod metadata {
         ///
         /// * `subject` - The subject to remove all properties from.
         /// * `key` - The key of the property to be removed. A URI string.
-        pub fn property_remove(&self, subject: uuid, key: &str) -> Result<(), Error> {
-            let key = ffi::CString::new(key).expect("to create cstring from key");
-            map_error(|| unsafe { j::jack_remove_property(self.raw(), subject, key.as_ptr()) })
+        pub fn property_remove(&self, subject: Uuid, key: &str) -> Result<(), Error> {
+            unsafe { remove_property(self.raw(), subject, key) }
         }
 
         /// Remove all properties from a subject.
@@ -317,9 +347,9 @@ mod metadata {
         /// # Arguments
         ///
         /// * `subject` - The subject to remove all properties from.
-        pub fn property_remove_subject(&self, subject: uuid) -> Result<(), Error> {
+        pub fn property_remove_subject(&self, subject: Uuid) -> Result<(), Error> {
             unsafe {
-                if j::jack_remove_properties(self.raw(), subject) == -1 {
+                if j::jack_remove_properties(self.raw(), subject.raw()) == -1 {
                     Err(Error::UnknownError { error_code: -1 })
                 } else {
                     Ok(())
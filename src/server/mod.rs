@@ -0,0 +1,13 @@
+//! In-process JACK server control through the `jackctl` API.
+//!
+//! The `jackctl` functions are exported by JACK2's server library. When the loaded JACK library
+//! does not export them, every function in this module returns
+//! [`Error::WeakFunctionNotFound`](crate::Error::WeakFunctionNotFound).
+mod parameter;
+mod server_impl;
+
+pub use self::parameter::{
+    Parameter, ParameterConstraint, ParameterType, ParameterValidationError, ParameterValue,
+    PARAMETER_STRING_MAX_SIZE,
+};
+pub use self::server_impl::{Driver, DriverType, Internal, Server, ServerState};
@@ -0,0 +1,493 @@
+use jack_sys as j;
+use std::marker::PhantomData;
+use std::{ffi, fmt};
+
+use crate::jack_utils::collect_jslist;
+use crate::Error;
+
+/// The maximum length of a string parameter value. Unlike the "C" JACK API, this does not take
+/// into account the final `NULL` character and instead corresponds directly to `.len()`.
+pub const PARAMETER_STRING_MAX_SIZE: usize = 127;
+
+/// A configurable option of a server, driver or internal client.
+///
+/// # Example
+/// ```no_run
+/// use jack::server::{Server, ParameterValue};
+///
+/// let server = Server::new().unwrap();
+/// let driver = server.driver_by_name("dummy").unwrap();
+/// for parameter in driver.parameters().unwrap() {
+///     println!("{:?}", parameter);
+/// }
+/// let rate = driver.parameter_by_name("rate").unwrap().unwrap();
+/// rate.set_value(&ParameterValue::UInt(48000)).unwrap();
+/// ```
+#[derive(Clone, Copy)]
+pub struct Parameter<'a> {
+    ptr: *mut j::jackctl_parameter_t,
+    _owner: PhantomData<&'a ()>,
+}
+
+/// The data type of a [`Parameter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterType {
+    Int,
+    UInt,
+    Char,
+    String,
+    Bool,
+}
+
+/// The value of a [`Parameter`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd)]
+pub enum ParameterValue {
+    Int(i32),
+    UInt(u32),
+    Char(char),
+    String(String),
+    Bool(bool),
+}
+
+/// The values that a [`Parameter`] may take.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParameterConstraint {
+    /// The value must be between `min` and `max`, inclusive.
+    Range {
+        min: ParameterValue,
+        max: ParameterValue,
+    },
+    /// The value is one of the listed values.
+    Enum {
+        /// Each possible value along with its description.
+        values: Vec<(ParameterValue, String)>,
+        /// If `true`, values outside of `values` are not accepted.
+        is_strict: bool,
+        /// If `true`, the values are only suggestions and are not meaningful by themselves, for
+        /// example, a list of detected devices.
+        is_fake_value: bool,
+    },
+}
+
+/// An error validating a [`ParameterValue`] against a [`Parameter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParameterValidationError {
+    /// The value does not have the type of the parameter.
+    TypeMismatch {
+        expected: ParameterType,
+        actual: ParameterType,
+    },
+    /// The value is outside of the range constraint of the parameter.
+    OutOfRange,
+    /// The value is not one of the values of a strict enum constraint.
+    NotInEnum,
+    /// A string value is longer than [`PARAMETER_STRING_MAX_SIZE`].
+    StringTooLong,
+    /// A string value contains a nul byte.
+    ContainsNul,
+    /// A char value can not be represented as a single byte.
+    CharNotAscii,
+}
+
+impl std::fmt::Display for ParameterValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ParameterValidationError: {:?}", &self)
+    }
+}
+
+impl std::error::Error for ParameterValidationError {}
+
+impl ParameterValue {
+    /// The type of the value.
+    pub fn parameter_type(&self) -> ParameterType {
+        match self {
+            ParameterValue::Int(_) => ParameterType::Int,
+            ParameterValue::UInt(_) => ParameterType::UInt,
+            ParameterValue::Char(_) => ParameterType::Char,
+            ParameterValue::String(_) => ParameterType::String,
+            ParameterValue::Bool(_) => ParameterType::Bool,
+        }
+    }
+
+    unsafe fn from_ffi(typ: ParameterType, mut value: j::Union_jackctl_parameter_value) -> Self {
+        match typ {
+            ParameterType::Int => ParameterValue::Int(*value.i()),
+            ParameterType::UInt => ParameterValue::UInt(*value.ui()),
+            ParameterType::Char => ParameterValue::Char(*value.c() as u8 as char),
+            ParameterType::String => ParameterValue::String(
+                ffi::CStr::from_ptr((*value.str()).as_ptr())
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ParameterType::Bool => ParameterValue::Bool(*value.b() != 0),
+        }
+    }
+
+    fn to_ffi(&self) -> Result<j::Union_jackctl_parameter_value, ParameterValidationError> {
+        let mut value = j::Union_jackctl_parameter_value::default();
+        unsafe {
+            match self {
+                ParameterValue::Int(v) => *value.i() = *v,
+                ParameterValue::UInt(v) => *value.ui() = *v,
+                ParameterValue::Char(v) => {
+                    if !v.is_ascii() {
+                        return Err(ParameterValidationError::CharNotAscii);
+                    }
+                    *value.c() = *v as u8 as libc::c_char;
+                }
+                ParameterValue::String(v) => {
+                    if v.len() > PARAMETER_STRING_MAX_SIZE {
+                        return Err(ParameterValidationError::StringTooLong);
+                    }
+                    if v.contains('\0') {
+                        return Err(ParameterValidationError::ContainsNul);
+                    }
+                    let dst = &mut *value.str();
+                    for (d, s) in dst.iter_mut().zip(v.bytes()) {
+                        *d = s as libc::c_char;
+                    }
+                }
+                ParameterValue::Bool(v) => *value.b() = u8::from(*v),
+            }
+        }
+        Ok(value)
+    }
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterValue::Int(v) => write!(f, "{v}"),
+            ParameterValue::UInt(v) => write!(f, "{v}"),
+            ParameterValue::Char(v) => write!(f, "{v}"),
+            ParameterValue::String(v) => write!(f, "{v}"),
+            ParameterValue::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl ParameterConstraint {
+    /// Returns `Ok(())` if `value` satisfies the constraint.
+    pub fn validate(&self, value: &ParameterValue) -> Result<(), ParameterValidationError> {
+        match self {
+            ParameterConstraint::Range { min, max } => {
+                if value < min || value > max {
+                    Err(ParameterValidationError::OutOfRange)
+                } else {
+                    Ok(())
+                }
+            }
+            ParameterConstraint::Enum {
+                values,
+                is_strict: true,
+                ..
+            } => {
+                if values.iter().any(|(v, _)| v == value) {
+                    Ok(())
+                } else {
+                    Err(ParameterValidationError::NotInEnum)
+                }
+            }
+            ParameterConstraint::Enum { .. } => Ok(()),
+        }
+    }
+}
+
+impl<'a> Parameter<'a> {
+    /// Collect the parameters in a jackctl parameter list.
+    ///
+    /// # Safety
+    /// `list` must be a parameter list that is valid for the lifetime `'a`.
+    pub(crate) unsafe fn from_list(list: *const j::JSList) -> Vec<Parameter<'a>> {
+        collect_jslist(list)
+            .into_iter()
+            .map(|ptr| Parameter {
+                ptr,
+                _owner: PhantomData,
+            })
+            .collect()
+    }
+
+    /// The name of the parameter, for example `"rate"`.
+    pub fn name(&self) -> Result<String, Error> {
+        let s = unsafe { j::jackctl_parameter_get_name(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_get_name"))?;
+        Ok(unsafe { ffi::CStr::from_ptr(s).to_string_lossy().into_owned() })
+    }
+
+    /// A one line description of the parameter.
+    pub fn short_description(&self) -> Result<String, Error> {
+        let s = unsafe { j::jackctl_parameter_get_short_description(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_get_short_description"),
+        )?;
+        Ok(unsafe { ffi::CStr::from_ptr(s).to_string_lossy().into_owned() })
+    }
+
+    /// A detailed description of the parameter.
+    pub fn long_description(&self) -> Result<String, Error> {
+        let s = unsafe { j::jackctl_parameter_get_long_description(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_get_long_description"),
+        )?;
+        Ok(unsafe { ffi::CStr::from_ptr(s).to_string_lossy().into_owned() })
+    }
+
+    /// The commandline flag of the parameter, for example `'r'` for `-r44100`.
+    pub fn id(&self) -> Result<char, Error> {
+        let id = unsafe { j::jackctl_parameter_get_id(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_get_id"))?;
+        Ok(id as u8 as char)
+    }
+
+    /// The type of the values that the parameter holds.
+    pub fn parameter_type(&self) -> Result<ParameterType, Error> {
+        let typ = unsafe { j::jackctl_parameter_get_type(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_get_type"))?;
+        match typ {
+            j::JackParamInt => Ok(ParameterType::Int),
+            j::JackParamUInt => Ok(ParameterType::UInt),
+            j::JackParamChar => Ok(ParameterType::Char),
+            j::JackParamString => Ok(ParameterType::String),
+            j::JackParamBool => Ok(ParameterType::Bool),
+            error_code => Err(Error::UnknownError {
+                error_code: error_code as _,
+            }),
+        }
+    }
+
+    /// The current value of the parameter.
+    pub fn value(&self) -> Result<ParameterValue, Error> {
+        let typ = self.parameter_type()?;
+        let value = unsafe { j::jackctl_parameter_get_value(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_get_value"))?;
+        Ok(unsafe { ParameterValue::from_ffi(typ, value) })
+    }
+
+    /// The value that the parameter has if it is not set.
+    pub fn default_value(&self) -> Result<ParameterValue, Error> {
+        let typ = self.parameter_type()?;
+        let value = unsafe { j::jackctl_parameter_get_default_value(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_get_default_value"),
+        )?;
+        Ok(unsafe { ParameterValue::from_ffi(typ, value) })
+    }
+
+    /// Returns `true` if the value has been explicitly set.
+    pub fn is_set(&self) -> Result<bool, Error> {
+        let res = unsafe { j::jackctl_parameter_is_set(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_is_set"))?;
+        Ok(res != 0)
+    }
+
+    /// Restore the default value of the parameter.
+    pub fn reset(&self) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_parameter_reset(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_reset"))?;
+        match res {
+            0 => Err(Error::ParameterSetError(self.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The values that the parameter may take, if the parameter is constrained.
+    pub fn constraint(&self) -> Result<Option<ParameterConstraint>, Error> {
+        let typ = self.parameter_type()?;
+        let has_range = unsafe { j::jackctl_parameter_has_range_constraint(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_has_range_constraint"),
+        )?;
+        if has_range != 0 {
+            let mut min = j::Union_jackctl_parameter_value::default();
+            let mut max = j::Union_jackctl_parameter_value::default();
+            unsafe { j::jackctl_parameter_get_range_constraint(self.ptr, &mut min, &mut max) }
+                .ok_or(Error::WeakFunctionNotFound(
+                    "jackctl_parameter_get_range_constraint",
+                ))?;
+            return Ok(Some(ParameterConstraint::Range {
+                min: unsafe { ParameterValue::from_ffi(typ, min) },
+                max: unsafe { ParameterValue::from_ffi(typ, max) },
+            }));
+        }
+        let has_enum = unsafe { j::jackctl_parameter_has_enum_constraint(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_has_enum_constraint"),
+        )?;
+        if has_enum == 0 {
+            return Ok(None);
+        }
+        let count = unsafe { j::jackctl_parameter_get_enum_constraints_count(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_get_enum_constraints_count"),
+        )?;
+        let mut values = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let value = unsafe { j::jackctl_parameter_get_enum_constraint_value(self.ptr, index) }
+                .ok_or(Error::WeakFunctionNotFound(
+                    "jackctl_parameter_get_enum_constraint_value",
+                ))?;
+            let description =
+                unsafe { j::jackctl_parameter_get_enum_constraint_description(self.ptr, index) }
+                    .ok_or(Error::WeakFunctionNotFound(
+                        "jackctl_parameter_get_enum_constraint_description",
+                    ))?;
+            values.push(unsafe {
+                (
+                    ParameterValue::from_ffi(typ, value),
+                    ffi::CStr::from_ptr(description)
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            });
+        }
+        let is_strict = unsafe { j::jackctl_parameter_constraint_is_strict(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_parameter_constraint_is_strict"),
+        )?;
+        let is_fake_value = unsafe { j::jackctl_parameter_constraint_is_fake_value(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound(
+                "jackctl_parameter_constraint_is_fake_value",
+            ))?;
+        Ok(Some(ParameterConstraint::Enum {
+            values,
+            is_strict: is_strict != 0,
+            is_fake_value: is_fake_value != 0,
+        }))
+    }
+
+    /// Returns `Ok(())` if `value` has the type of the parameter and satisfies its constraint.
+    pub fn validate(&self, value: &ParameterValue) -> Result<(), Error> {
+        let invalid = |reason| -> Result<(), Error> {
+            Err(Error::InvalidParameterValue {
+                parameter: self.name()?,
+                reason,
+            })
+        };
+        let expected = self.parameter_type()?;
+        let actual = value.parameter_type();
+        if expected != actual {
+            return invalid(ParameterValidationError::TypeMismatch { expected, actual });
+        }
+        if let Err(reason) = value.to_ffi() {
+            return invalid(reason);
+        }
+        if let Some(constraint) = self.constraint()? {
+            if let Err(reason) = constraint.validate(value) {
+                return invalid(reason);
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the value of the parameter. The value is validated with `validate` before it is set.
+    ///
+    /// `Err(Error::InvalidParameterValue)` is returned if the value is not valid and
+    /// `Err(Error::ParameterSetError)` is returned if JACK rejected the value.
+    pub fn set_value(&self, value: &ParameterValue) -> Result<(), Error> {
+        self.validate(value)?;
+        let ffi_value = value.to_ffi().unwrap();
+        let res = unsafe { j::jackctl_parameter_set_value(self.ptr, &ffi_value) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_parameter_set_value"))?;
+        match res {
+            0 => Err(Error::ParameterSetError(self.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Expose the underlying ffi pointer.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    #[inline(always)]
+    pub fn raw(&self) -> *mut j::jackctl_parameter_t {
+        self.ptr
+    }
+}
+
+impl fmt::Debug for Parameter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Parameter")
+            .field("name", &self.name())
+            .field("id", &self.id())
+            .field("short_description", &self.short_description())
+            .field("value", &self.value())
+            .field("default_value", &self.default_value())
+            .field("constraint", &self.constraint())
+            .finish()
+    }
+}
+
+/// Find the parameter with `name` within `parameters`.
+pub(crate) fn find_parameter<'a>(
+    parameters: Vec<Parameter<'a>>,
+    name: &str,
+) -> Result<Option<Parameter<'a>>, Error> {
+    for parameter in parameters {
+        if parameter.name()? == name {
+            return Ok(Some(parameter));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_constraint_rejects_out_of_range_values() {
+        let constraint = ParameterConstraint::Range {
+            min: ParameterValue::UInt(16),
+            max: ParameterValue::UInt(8192),
+        };
+        assert_eq!(constraint.validate(&ParameterValue::UInt(1024)), Ok(()));
+        assert_eq!(
+            constraint.validate(&ParameterValue::UInt(8)),
+            Err(ParameterValidationError::OutOfRange)
+        );
+        assert_eq!(
+            constraint.validate(&ParameterValue::UInt(16384)),
+            Err(ParameterValidationError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn strict_enum_constraint_rejects_unlisted_values() {
+        let values = vec![
+            (ParameterValue::Char('n'), "none".to_string()),
+            (ParameterValue::Char('r'), "rectangular".to_string()),
+        ];
+        let strict = ParameterConstraint::Enum {
+            values: values.clone(),
+            is_strict: true,
+            is_fake_value: false,
+        };
+        assert_eq!(strict.validate(&ParameterValue::Char('r')), Ok(()));
+        assert_eq!(
+            strict.validate(&ParameterValue::Char('x')),
+            Err(ParameterValidationError::NotInEnum)
+        );
+        let loose = ParameterConstraint::Enum {
+            values,
+            is_strict: false,
+            is_fake_value: true,
+        };
+        assert_eq!(loose.validate(&ParameterValue::Char('x')), Ok(()));
+    }
+
+    #[test]
+    fn values_round_trip_through_ffi() {
+        for value in [
+            ParameterValue::Int(-3),
+            ParameterValue::UInt(48000),
+            ParameterValue::Char('s'),
+            ParameterValue::String("hw:0".to_string()),
+            ParameterValue::Bool(true),
+        ] {
+            let ffi_value = value.to_ffi().unwrap();
+            let round_trip = unsafe { ParameterValue::from_ffi(value.parameter_type(), ffi_value) };
+            assert_eq!(round_trip, value);
+        }
+        assert_eq!(
+            ParameterValue::String("x".repeat(128)).to_ffi().err(),
+            Some(ParameterValidationError::StringTooLong)
+        );
+        assert_eq!(
+            ParameterValue::String("hw:\0".to_string()).to_ffi().err(),
+            Some(ParameterValidationError::ContainsNul)
+        );
+    }
+}
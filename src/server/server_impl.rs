@@ -0,0 +1,431 @@
+use jack_sys as j;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::{ffi, fmt, ptr};
+
+use crate::jack_utils::collect_jslist;
+use crate::server::parameter::find_parameter;
+use crate::server::Parameter;
+use crate::Error;
+
+/// The lifecycle stage of a [`Server`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerState {
+    /// The server has been created but no driver has been opened.
+    Created,
+    /// A driver has been opened but the server is not processing.
+    Opened,
+    /// The server is running and accepting clients.
+    Started,
+}
+
+/// A JACK server that runs inside of the current process.
+///
+/// # Example
+/// ```no_run
+/// let server = jack::server::Server::new().unwrap();
+/// let driver = server.driver_by_name("dummy").unwrap();
+/// driver.params_parse(&["-r", "44100", "-p", "1024"]).unwrap();
+/// server.open(&driver).unwrap();
+/// server.start().unwrap();
+/// // Clients may now connect with `jack::Client::new`.
+/// server.stop().unwrap();
+/// ```
+pub struct Server {
+    ptr: *mut j::jackctl_server_t,
+    state: Cell<ServerState>,
+}
+
+unsafe impl Send for Server {}
+
+/// The role of a driver within a server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DriverType {
+    /// A driver that runs the server's process cycle.
+    Master,
+    /// A driver that follows the cycle of the master driver.
+    Slave,
+}
+
+/// A backend that a [`Server`] can use to drive its process cycle, for example `"dummy"`,
+/// `"alsa"`, or `"net"`.
+#[derive(Clone, Copy)]
+pub struct Driver<'a> {
+    ptr: *mut j::jackctl_driver_t,
+    _server: PhantomData<&'a Server>,
+}
+
+/// An internal client that may be loaded into a [`Server`].
+#[derive(Clone, Copy)]
+pub struct Internal<'a> {
+    ptr: *mut j::jackctl_internal_t,
+    _server: PhantomData<&'a Server>,
+}
+
+impl Server {
+    /// Create a new server. The server does nothing until a driver is opened with `open` and the
+    /// server is started with `start`.
+    ///
+    /// `Err(Error::ServerCreationError)` is returned if JACK failed to create the server.
+    pub fn new() -> Result<Self, Error> {
+        #[cfg(feature = "dynamic_loading")]
+        if let Err(err) = jack_sys::library() {
+            return Err(Error::LibraryError(err.to_string()));
+        }
+
+        crate::logging::maybe_init_logging();
+        let ptr = unsafe { j::jackctl_server_create(None, None) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_create"))?;
+        if ptr.is_null() {
+            Err(Error::ServerCreationError)
+        } else {
+            Ok(Server {
+                ptr,
+                state: Cell::new(ServerState::Created),
+            })
+        }
+    }
+
+    /// The current lifecycle stage of the server.
+    pub fn state(&self) -> ServerState {
+        self.state.get()
+    }
+
+    /// The server's own parameters, for example `"name"`, `"realtime"` and `"sync"`.
+    pub fn parameters(&self) -> Result<Vec<Parameter<'_>>, Error> {
+        let list = unsafe { j::jackctl_server_get_parameters(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_get_parameters"))?;
+        Ok(unsafe { Parameter::from_list(list) })
+    }
+
+    /// Get one of the server's parameters by its name.
+    pub fn parameter_by_name(&self, name: &str) -> Result<Option<Parameter<'_>>, Error> {
+        find_parameter(self.parameters()?, name)
+    }
+
+    /// All the drivers that are available to the server.
+    pub fn drivers(&self) -> Result<Vec<Driver<'_>>, Error> {
+        let list = unsafe { j::jackctl_server_get_drivers_list(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_server_get_drivers_list"),
+        )?;
+        Ok(unsafe { collect_jslist(list) }
+            .into_iter()
+            .map(|ptr| Driver {
+                ptr,
+                _server: PhantomData,
+            })
+            .collect())
+    }
+
+    /// Get a driver by its name, for example `"dummy"` or `"alsa"`.
+    ///
+    /// `Err(Error::DriverNotFound)` is returned if no driver with `name` exists.
+    pub fn driver_by_name(&self, name: &str) -> Result<Driver<'_>, Error> {
+        for driver in self.drivers()? {
+            if driver.name()? == name {
+                return Ok(driver);
+            }
+        }
+        Err(Error::DriverNotFound(name.to_string()))
+    }
+
+    /// All the internal clients that are available to the server.
+    pub fn internals(&self) -> Result<Vec<Internal<'_>>, Error> {
+        let list = unsafe { j::jackctl_server_get_internals_list(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_server_get_internals_list"),
+        )?;
+        Ok(unsafe { collect_jslist(list) }
+            .into_iter()
+            .map(|ptr| Internal {
+                ptr,
+                _server: PhantomData,
+            })
+            .collect())
+    }
+
+    /// Get an internal client by its name, for example `"netmanager"` or `"profiler"`.
+    pub fn internal_by_name(&self, name: &str) -> Result<Option<Internal<'_>>, Error> {
+        for internal in self.internals()? {
+            if internal.name()? == name {
+                return Ok(Some(internal));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Open the server using `driver` as the master driver.
+    ///
+    /// `Err(Error::ServerOpenError)` is returned if the server is not in the `Created` state or if
+    /// the driver could not be opened.
+    pub fn open(&self, driver: &Driver) -> Result<(), Error> {
+        if self.state() != ServerState::Created {
+            return Err(Error::ServerOpenError);
+        }
+        let res = unsafe { j::jackctl_server_open(self.ptr, driver.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_open"))?;
+        match res {
+            0 => Err(Error::ServerOpenError),
+            _ => {
+                self.state.set(ServerState::Opened);
+                Ok(())
+            }
+        }
+    }
+
+    /// Start processing. After this call, clients may connect to the server.
+    ///
+    /// `Err(Error::ServerStartError)` is returned if the server is not in the `Opened` state or
+    /// if the server failed to start.
+    pub fn start(&self) -> Result<(), Error> {
+        if self.state() != ServerState::Opened {
+            return Err(Error::ServerStartError);
+        }
+        let res = unsafe { j::jackctl_server_start(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_start"))?;
+        match res {
+            0 => Err(Error::ServerStartError),
+            _ => {
+                self.state.set(ServerState::Started);
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop processing. The server may be started again with `start`.
+    ///
+    /// `Err(Error::ServerStopError)` is returned if the server is not in the `Started` state or if
+    /// the server failed to stop.
+    pub fn stop(&self) -> Result<(), Error> {
+        if self.state() != ServerState::Started {
+            return Err(Error::ServerStopError);
+        }
+        let res = unsafe { j::jackctl_server_stop(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_stop"))?;
+        match res {
+            0 => Err(Error::ServerStopError),
+            _ => {
+                self.state.set(ServerState::Opened);
+                Ok(())
+            }
+        }
+    }
+
+    /// Close the driver that was opened with `open`. The server may be opened again afterwards,
+    /// possibly with a different driver.
+    ///
+    /// `Err(Error::ServerCloseError)` is returned if the server is not in the `Opened` state or if
+    /// the driver failed to close.
+    pub fn close(&self) -> Result<(), Error> {
+        if self.state() != ServerState::Opened {
+            return Err(Error::ServerCloseError);
+        }
+        let res = unsafe { j::jackctl_server_close(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_close"))?;
+        match res {
+            0 => Err(Error::ServerCloseError),
+            _ => {
+                self.state.set(ServerState::Created);
+                Ok(())
+            }
+        }
+    }
+
+    /// Load an internal client into the running server.
+    ///
+    /// `Err(Error::InternalClientError(name))` is returned if the internal client failed to load.
+    pub fn load_internal(&self, internal: &Internal) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_server_load_internal(self.ptr, internal.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_load_internal"))?;
+        match res {
+            0 => Err(Error::InternalClientError(internal.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Unload an internal client that was loaded with `load_internal`.
+    ///
+    /// `Err(Error::InternalClientError(name))` is returned if the internal client failed to
+    /// unload.
+    pub fn unload_internal(&self, internal: &Internal) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_server_unload_internal(self.ptr, internal.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_server_unload_internal"),
+        )?;
+        match res {
+            0 => Err(Error::InternalClientError(internal.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add `driver` as a slave driver of the server.
+    ///
+    /// `Err(Error::SlaveDriverError(name))` is returned if the driver could not be added.
+    pub fn add_slave(&self, driver: &Driver) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_server_add_slave(self.ptr, driver.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_add_slave"))?;
+        match res {
+            0 => Err(Error::SlaveDriverError(driver.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove a slave driver that was added with `add_slave`.
+    ///
+    /// `Err(Error::SlaveDriverError(name))` is returned if the driver could not be removed.
+    pub fn remove_slave(&self, driver: &Driver) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_server_remove_slave(self.ptr, driver.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_remove_slave"))?;
+        match res {
+            0 => Err(Error::SlaveDriverError(driver.name()?)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace the master driver of the running server with `driver`.
+    pub fn switch_master(&self, driver: &Driver) -> Result<(), Error> {
+        let res = unsafe { j::jackctl_server_switch_master(self.ptr, driver.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_server_switch_master"))?;
+        match res {
+            0 => Err(Error::ServerOpenError),
+            _ => Ok(()),
+        }
+    }
+
+    /// Expose the underlying ffi pointer.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    #[inline(always)]
+    pub fn raw(&self) -> *mut j::jackctl_server_t {
+        self.ptr
+    }
+}
+
+/// Stop, close, and destroy the server.
+impl Drop for Server {
+    fn drop(&mut self) {
+        // Best effort shutdown.
+        if self.state() == ServerState::Started {
+            let _ = self.stop();
+        }
+        if self.state() == ServerState::Opened {
+            let _ = self.close();
+        }
+        unsafe { j::jackctl_server_destroy(self.ptr) };
+        self.ptr = ptr::null_mut();
+    }
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Server")
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+impl<'a> Driver<'a> {
+    /// The name of the driver, for example `"dummy"`.
+    pub fn name(&self) -> Result<String, Error> {
+        let name = unsafe { j::jackctl_driver_get_name(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_driver_get_name"))?;
+        Ok(unsafe { ffi::CStr::from_ptr(name).to_string_lossy().into_owned() })
+    }
+
+    /// Whether the driver is a master or slave driver.
+    pub fn driver_type(&self) -> Result<DriverType, Error> {
+        let typ = unsafe { j::jackctl_driver_get_type(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_driver_get_type"))?;
+        match typ {
+            j::JackSlave => Ok(DriverType::Slave),
+            _ => Ok(DriverType::Master),
+        }
+    }
+
+    /// The parameters of the driver, for example `"rate"` and `"period"` for the dummy driver.
+    pub fn parameters(&self) -> Result<Vec<Parameter<'a>>, Error> {
+        let list = unsafe { j::jackctl_driver_get_parameters(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_driver_get_parameters"))?;
+        Ok(unsafe { Parameter::from_list(list) })
+    }
+
+    /// Get one of the driver's parameters by its name.
+    pub fn parameter_by_name(&self, name: &str) -> Result<Option<Parameter<'a>>, Error> {
+        find_parameter(self.parameters()?, name)
+    }
+
+    /// Configure the driver with `jackd` style commandline arguments, for example
+    /// `&["-r", "44100", "-p", "1024"]` for the dummy driver.
+    ///
+    /// # Panics
+    /// Panics if any of the arguments can not be converted to a `CString`.
+    pub fn params_parse(&self, args: &[&str]) -> Result<(), Error> {
+        // The arguments are parsed with getopt, which skips the first argument.
+        let args: Vec<ffi::CString> = std::iter::once(self.name()?.as_str())
+            .chain(args.iter().copied())
+            .map(|arg| ffi::CString::new(arg).unwrap())
+            .collect();
+        let mut argv: Vec<*mut libc::c_char> =
+            args.iter().map(|arg| arg.as_ptr() as *mut _).collect();
+        let res =
+            unsafe { j::jackctl_driver_params_parse(self.ptr, argv.len() as _, argv.as_mut_ptr()) }
+                .ok_or(Error::WeakFunctionNotFound("jackctl_driver_params_parse"))?;
+        match res {
+            0 => Ok(()),
+            error_code => Err(Error::UnknownError { error_code }),
+        }
+    }
+
+    /// Expose the underlying ffi pointer.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    #[inline(always)]
+    pub fn raw(&self) -> *mut j::jackctl_driver_t {
+        self.ptr
+    }
+}
+
+impl fmt::Debug for Driver<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Driver")
+            .field("name", &self.name())
+            .field("driver_type", &self.driver_type())
+            .finish()
+    }
+}
+
+impl<'a> Internal<'a> {
+    /// The name of the internal client, for example `"netmanager"`.
+    pub fn name(&self) -> Result<String, Error> {
+        let name = unsafe { j::jackctl_internal_get_name(self.ptr) }
+            .ok_or(Error::WeakFunctionNotFound("jackctl_internal_get_name"))?;
+        Ok(unsafe { ffi::CStr::from_ptr(name).to_string_lossy().into_owned() })
+    }
+
+    /// The parameters of the internal client.
+    pub fn parameters(&self) -> Result<Vec<Parameter<'a>>, Error> {
+        let list = unsafe { j::jackctl_internal_get_parameters(self.ptr) }.ok_or(
+            Error::WeakFunctionNotFound("jackctl_internal_get_parameters"),
+        )?;
+        Ok(unsafe { Parameter::from_list(list) })
+    }
+
+    /// Get one of the internal client's parameters by its name.
+    pub fn parameter_by_name(&self, name: &str) -> Result<Option<Parameter<'a>>, Error> {
+        find_parameter(self.parameters()?, name)
+    }
+
+    /// Expose the underlying ffi pointer.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    #[inline(always)]
+    pub fn raw(&self) -> *mut j::jackctl_internal_t {
+        self.ptr
+    }
+}
+
+impl fmt::Debug for Internal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Internal")
+            .field("name", &self.name())
+            .finish()
+    }
+}
@@ -9,7 +9,7 @@ fn client_can_open() {
     assert_ne!(client.sample_rate(), 0);
     assert_ne!(client.buffer_size(), 0);
     assert_ne!(client.uuid_string(), "");
-    assert_ne!(client.uuid(), 0);
+    assert!(!client.uuid().is_empty());
     let cpu_load = client.cpu_load();
     assert!(cpu_load > 0.0, "client.cpu_load() = {}", cpu_load);
 }
@@ -46,8 +46,8 @@ fn client_uuid_are_unique() {
     assert_ne!(client1.uuid_string(), "");
     assert_ne!(client2.uuid_string(), "");
     assert_ne!(client1.uuid_string(), client2.uuid_string());
-    assert_ne!(client1.uuid(), 0);
-    assert_ne!(client2.uuid(), 0);
+    assert!(!client1.uuid().is_empty());
+    assert!(!client2.uuid().is_empty());
     assert_ne!(client1.uuid(), client2.uuid());
 }
 
@@ -86,3 +86,97 @@ fn nonexistant_uuid_to_client_name_returns_none() {
     assert_eq!(DEFAULT_TEST_CLIENT.name_by_uuid_str(&uuid_string), None);
     assert_eq!(DEFAULT_TEST_CLIENT.name_by_uuid(uuid), None);
 }
+
+#[test]
+fn real_time_priority_is_only_set_for_realtime_clients() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    assert_eq!(client.is_realtime(), client.real_time_priority().is_some());
+    assert_eq!(
+        client.is_realtime(),
+        client.max_real_time_priority().is_some()
+    );
+}
+
+#[test]
+fn spawned_realtime_thread_returns_value_on_join() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let priority = client.real_time_priority().unwrap_or(0);
+    let handle = client.spawn_realtime_thread(priority, || 1 + 1).unwrap();
+    assert_eq!(handle.join(), Ok(2));
+}
+
+#[test]
+fn panic_in_spawned_realtime_thread_is_returned_on_join() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let handle = client
+        .spawn_realtime_thread(0, || panic!("intentional panic here"))
+        .unwrap();
+    assert_eq!(handle.join(), Err::<(), _>(crate::Error::ThreadPanicked));
+}
+
+#[test]
+fn joining_realtime_thread_of_closed_client_fails() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let handle = client.spawn_realtime_thread(0, || 1 + 1).unwrap();
+    drop(client);
+    assert_eq!(handle.join(), Err(crate::Error::ClientIsNoLongerAlive));
+}
+
+#[test]
+fn max_delayed_usecs_can_be_reset() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    assert!(client.xrun_delayed_usecs() >= 0.0);
+    assert!(client.max_delayed_usecs() >= 0.0);
+    client.reset_max_delayed_usecs();
+    assert!(client.max_delayed_usecs() >= 0.0);
+}
+
+#[test]
+fn xrun_monitor_records_xruns_until_reset() {
+    use crate::contrib::xrun::{XrunMonitor, XrunStats};
+    use crate::NotificationHandler;
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let monitor = XrunMonitor::new();
+    assert_eq!(monitor.snapshot(), XrunStats::default());
+
+    let before = client.time();
+    monitor.clone().xrun(&client);
+    monitor.record(&client);
+    let stats = monitor.snapshot();
+    assert_eq!(stats.count, 2);
+    assert!(stats.max_delay_usecs >= stats.last_delay_usecs);
+    assert!(stats.last_xrun_time.unwrap() >= before);
+
+    monitor.reset();
+    assert_eq!(monitor.snapshot(), XrunStats::default());
+}
+
+#[test]
+fn ports_of_the_same_client_can_be_tied_and_untied() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::AudioIn::default())
+        .unwrap();
+    let output = client
+        .register_port("out", crate::AudioOut::default())
+        .unwrap();
+    let tie = client.tie_ports(&input, &output).unwrap();
+    tie.untie().unwrap();
+}
+
+#[test]
+fn ports_of_different_clients_can_not_be_tied() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let (other, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::AudioIn::default())
+        .unwrap();
+    let output = other
+        .register_port("out", crate::AudioOut::default())
+        .unwrap();
+    assert_eq!(
+        client.tie_ports(&input, &output).err(),
+        Some(crate::Error::PortTieError)
+    );
+}
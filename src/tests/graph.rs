@@ -0,0 +1,188 @@
+use crate::{AudioIn, AudioOut, Client, ClientOptions, Connection, Graph, PortFlags};
+
+#[test]
+fn graph_contains_registered_ports_and_connections() {
+    let (client, _) = Client::new("graph-client", ClientOptions::default()).unwrap();
+    let out = client.register_port("out", AudioOut::default()).unwrap();
+    let input = client.register_port("in", AudioIn::default()).unwrap();
+    let client = client.activate_async((), ()).unwrap();
+    let before = client.as_client().graph();
+
+    let graph_client = &before.clients["graph-client"];
+    assert_eq!(graph_client.uuid, Some(client.as_client().uuid()));
+    let port = &before.ports["graph-client:out"];
+    assert_eq!(port.client, "graph-client");
+    assert_eq!(port.port_type, "32 bit float mono audio");
+    assert!(port.flags.contains(PortFlags::IS_OUTPUT));
+    assert_eq!(port.uuid, out.uuid().unwrap());
+    assert_eq!(before.client_ports("graph-client").count(), 2);
+
+    client.as_client().connect_ports(&out, &input).unwrap();
+    let after = client.as_client().graph();
+    let connection = Connection::new("graph-client:out", "graph-client:in");
+    assert!(after.connections.contains(&connection));
+    let diff = Graph::diff(&before, &after);
+    assert_eq!(diff.added_connections, [connection]);
+    assert!(diff.added_ports.is_empty() && diff.removed_ports.is_empty());
+}
+
+#[test]
+fn graph_tracker_follows_ports_and_connections() {
+    use crate::contrib::graph_tracker::{GraphChange, GraphTracker};
+    use std::time::Duration;
+
+    let (client, _) = Client::new("graph-tracker", ClientOptions::default()).unwrap();
+    let tracker = GraphTracker::new(&client);
+    let changes = tracker.subscribe();
+    let client = client.activate_async(tracker.clone(), ()).unwrap();
+
+    let (other, _) = Client::new("tracked", ClientOptions::default()).unwrap();
+    let out = other.register_port("out", AudioOut::default()).unwrap();
+    let mut input = other.register_port("in", AudioIn::default()).unwrap();
+    other.connect_ports(&out, &input).unwrap();
+    input.set_name("renamed").unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    let connection = Connection::new("tracked:out", "tracked:renamed");
+    assert!(tracker.graph().connections.contains(&connection));
+    let received: Vec<GraphChange> = changes.try_iter().collect();
+    assert!(received.contains(&GraphChange::ClientAdded("tracked".to_string())));
+    assert!(received.contains(&GraphChange::PortAdded("tracked:out".to_string())));
+    assert!(received.contains(&GraphChange::PortRenamed {
+        old_name: "tracked:in".to_string(),
+        new_name: "tracked:renamed".to_string(),
+    }));
+    assert_eq!(tracker.graph().clients["tracked"].uuid, None);
+    tracker.resolve_clients(client.as_client());
+    assert_eq!(tracker.graph().clients["tracked"].uuid, Some(other.uuid()));
+
+    drop(other);
+    std::thread::sleep(Duration::from_millis(100));
+    let graph = tracker.graph();
+    assert!(!graph.clients.contains_key("tracked"));
+    assert!(!graph.ports.contains_key("tracked:out"));
+    assert!(!graph.connections.contains(&connection));
+    let received: Vec<GraphChange> = changes.try_iter().collect();
+    assert!(received.contains(&GraphChange::PortRemoved("tracked:out".to_string())));
+    assert!(received.contains(&GraphChange::ClientRemoved("tracked".to_string())));
+    drop(client);
+}
+
+#[test]
+fn patchbay_restores_captured_connections() {
+    use crate::contrib::patchbay::Patchbay;
+
+    let (client, _) = Client::new("patchbay-client", ClientOptions::default()).unwrap();
+    let out = client.register_port("out", AudioOut::default()).unwrap();
+    let input = client.register_port("in", AudioIn::default()).unwrap();
+    let client = client.activate_async((), ()).unwrap();
+    client.as_client().connect_ports(&out, &input).unwrap();
+    let mut patchbay = Patchbay::capture(client.as_client(), Some("patchbay-cl.*"));
+    let connection = Connection::new("patchbay-client:out", "patchbay-client:in");
+    assert!(patchbay.connections.contains(&connection));
+    assert!(Patchbay::capture(client.as_client(), Some("patchbay"))
+        .connections
+        .is_empty());
+
+    client.as_client().disconnect(&out).unwrap();
+    patchbay
+        .connections
+        .insert(Connection::new("patchbay-client:out", "gone:in"));
+    let report = patchbay.restore(client.as_client());
+    assert_eq!(report.connected, vec![connection.clone()]);
+    assert_eq!(report.missing_ports, ["gone:in"]);
+    assert!(!report.is_complete());
+    assert!(out.is_connected_to("patchbay-client:in").unwrap());
+
+    let report = patchbay.restore(client.as_client());
+    assert_eq!(report.already_connected, [connection]);
+}
+
+#[test]
+fn autoconnector_applies_connect_and_block_rules() {
+    use crate::contrib::autoconnect::{self, Action, PortMatcher, Rule};
+    use crate::CvIn;
+    use std::time::Duration;
+
+    let rules = vec![
+        Rule::Connect {
+            output: PortMatcher::name("^autoconnected:out"),
+            input: PortMatcher::name("^autoconnected:"),
+        },
+        Rule::Block {
+            output: PortMatcher::any(),
+            input: PortMatcher::name("^autoconnected:blocked$"),
+        },
+    ];
+    let (notifications, mut connector) = autoconnect::channel(rules);
+    let (client, _) = Client::new("autoconnect-client", ClientOptions::default()).unwrap();
+    let client = client.activate_async(notifications, ()).unwrap();
+
+    let (other, _) = Client::new("autoconnected", ClientOptions::default()).unwrap();
+    let out = other.register_port("out", AudioOut::default()).unwrap();
+    let input = other.register_port("in", AudioIn::default()).unwrap();
+    let blocked = other.register_port("blocked", AudioIn::default()).unwrap();
+    let _cv = other.register_port("cv", CvIn::default()).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    let actions = connector
+        .process(client.as_client(), Duration::from_secs(1))
+        .unwrap();
+    let connection = Connection::new("autoconnected:out", "autoconnected:in");
+    assert!(actions.contains(&Action::Connected(connection)));
+    assert!(!actions
+        .iter()
+        .any(|action| matches!(action, Action::Connected(c) if c.input != "autoconnected:in")));
+    assert!(actions.iter().any(|action| matches!(
+        action,
+        Action::Failed(c, crate::Error::PortConnectionError { .. }) if c.input == "autoconnected:cv"
+    )));
+    assert!(out.is_connected_to("autoconnected:in").unwrap());
+
+    other.connect_ports(&out, &blocked).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    let actions = connector
+        .process(client.as_client(), Duration::from_secs(1))
+        .unwrap();
+    let connection = Connection::new("autoconnected:out", "autoconnected:blocked");
+    assert_eq!(actions, vec![Action::Disconnected(connection)]);
+    assert!(!out.is_connected_to("autoconnected:blocked").unwrap());
+    assert!(input.is_connected_to("autoconnected:out").unwrap());
+
+    let stop = connector.stop_handle();
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        stop.stop();
+    });
+    connector.run(client.as_client(), |_| {});
+    stopper.join().unwrap();
+
+    drop(client);
+    assert!(connector
+        .process(&other, Duration::from_millis(100))
+        .is_none());
+}
+
+#[test]
+fn graph_to_dot_uses_pretty_names() {
+    let (client, _) = Client::new("dot-client", ClientOptions::default()).unwrap();
+    let out = client.register_port("out", AudioOut::default()).unwrap();
+    let input = client.register_port("in", AudioIn::default()).unwrap();
+    out.set_pretty_name("Main Out").unwrap();
+    client.set_pretty_name("Dot Client").unwrap();
+    let client = client.activate_async((), ()).unwrap();
+    client.as_client().connect_ports(&out, &input).unwrap();
+    let graph = client.as_client().graph();
+
+    assert_eq!(
+        graph.ports["dot-client:out"].pretty_name.as_deref(),
+        Some("Main Out")
+    );
+    assert_eq!(
+        graph.clients["dot-client"].pretty_name.as_deref(),
+        Some("Dot Client")
+    );
+    let dot = graph.to_dot();
+    assert!(dot.contains("label=\"Dot Client\";"));
+    assert!(dot.contains("\"dot-client:out\" [label=\"Main Out\"];"));
+    assert!(dot.contains("\"dot-client:in\" [label=\"in\"];"));
+    assert!(dot.contains("\"dot-client:out\" -> \"dot-client:in\" [color=blue];"));
+}
@@ -0,0 +1,172 @@
+use crate::{EventType, SignalType};
+
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    png.extend_from_slice(&13u32.to_be_bytes());
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&width.to_be_bytes());
+    png.extend_from_slice(&height.to_be_bytes());
+    png
+}
+
+#[test]
+fn client_pretty_name_and_icons_roundtrip() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    assert_eq!(client.pretty_name(), None);
+    client.set_pretty_name("Pretty Client").unwrap();
+    assert_eq!(client.pretty_name(), Some("Pretty Client".to_string()));
+
+    assert_eq!(client.icon_small(), Ok(None));
+    client.set_icon_small(&png_header(32, 32)).unwrap();
+    assert_eq!(client.icon_small(), Ok(Some(png_header(32, 32))));
+    client.set_icon_large(&png_header(96, 96)).unwrap();
+    assert_eq!(client.icon_large(), Ok(Some(png_header(96, 96))));
+}
+
+#[test]
+fn icons_with_wrong_size_are_rejected() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    assert!(matches!(
+        client.set_icon_small(&png_header(96, 96)),
+        Err(crate::Error::InvalidPropertyValue { .. })
+    ));
+    assert!(matches!(
+        client.set_icon_large(b"not a png"),
+        Err(crate::Error::InvalidPropertyValue { .. })
+    ));
+    assert_eq!(client.icon_large(), Ok(None));
+}
+
+#[test]
+fn port_metadata_roundtrips() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let port = client
+        .register_port("out", crate::AudioOut::default())
+        .unwrap();
+    port.set_pretty_name("Left").unwrap();
+    port.set_order(3).unwrap();
+    port.set_signal_type(SignalType::Cv).unwrap();
+    port.set_port_group("Main").unwrap();
+    assert_eq!(port.pretty_name(), Ok(Some("Left".to_string())));
+    assert_eq!(port.order(), Ok(Some(3)));
+    assert_eq!(port.signal_type(), Ok(Some(SignalType::Cv)));
+    assert_eq!(port.port_group(), Ok(Some("Main".to_string())));
+}
+
+#[test]
+fn midi_ports_have_event_types_but_no_signal_type() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let port = client
+        .register_port("midi", crate::MidiIn::default())
+        .unwrap();
+    assert!(matches!(
+        port.set_signal_type(SignalType::Audio),
+        Err(crate::Error::InvalidPropertyValue { .. })
+    ));
+    assert_eq!(port.signal_type(), Ok(None));
+
+    let event_types = vec![EventType::Midi, EventType::Other("SYSEX".to_string())];
+    port.set_event_types(&event_types).unwrap();
+    assert_eq!(port.event_types(), Ok(Some(event_types)));
+    assert!(matches!(
+        port.set_event_types(&[EventType::Other("A,B".to_string())]),
+        Err(crate::Error::InvalidPropertyValue { .. })
+    ));
+}
+
+#[test]
+fn malformed_order_is_an_error() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let port = client
+        .register_port("in", crate::AudioIn::default())
+        .unwrap();
+    client
+        .property_set(
+            port.uuid().unwrap(),
+            crate::METADATA_ORDER,
+            &crate::Property::new("first", None),
+        )
+        .unwrap();
+    assert!(matches!(
+        port.order(),
+        Err(crate::Error::InvalidPropertyValue { .. })
+    ));
+}
+
+#[test]
+fn cv_ports_are_registered_with_cv_signal_type() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let cv_out = client
+        .register_port("cv_out", crate::CvOut::default())
+        .unwrap();
+    let audio_in = client
+        .register_port("audio_in", crate::AudioIn::default())
+        .unwrap();
+    assert_eq!(cv_out.signal_type(), Ok(Some(SignalType::Cv)));
+    assert_eq!(audio_in.signal_type(), Ok(None));
+
+    let cv_ports = client.ports_with_signal_type(None, SignalType::Cv, crate::PortFlags::empty());
+    let audio_ports =
+        client.ports_with_signal_type(None, SignalType::Audio, crate::PortFlags::empty());
+    assert!(cv_ports.contains(&cv_out.name().unwrap()));
+    assert!(!cv_ports.contains(&audio_in.name().unwrap()));
+    assert!(audio_ports.contains(&audio_in.name().unwrap()));
+    assert!(!audio_ports.contains(&cv_out.name().unwrap()));
+}
+
+#[test]
+fn clients_can_connect_cv_to_audio() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let cv_out = client
+        .register_port("cv_out", crate::CvOut::default())
+        .unwrap();
+    let cv_in = client
+        .register_port("cv_in", crate::CvIn::default())
+        .unwrap();
+    let audio_in = client
+        .register_port("audio_in", crate::AudioIn::default())
+        .unwrap();
+    let client = client.activate_async((), ()).unwrap();
+    // Only the auto-connector refuses to mix them, existing graphs must remain restorable.
+    client
+        .as_client()
+        .connect_ports(&cv_out, &audio_in)
+        .unwrap();
+    client.as_client().connect_ports(&cv_out, &cv_in).unwrap();
+}
+
+#[test]
+fn metadata_cache_tracks_changes_and_evicts_closed_clients() {
+    use crate::contrib::metadata_cache::MetadataCache;
+    use crate::{Property, PropertyChangeOwned};
+    use std::time::Duration;
+
+    let (mut client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let cache = MetadataCache::new(&client);
+    client
+        .register_property_change_handler(cache.clone())
+        .unwrap();
+    let changes = cache.subscribe();
+    let client = client.activate_async(cache.clone(), ()).unwrap();
+
+    let (other, _) = crate::Client::new("other", crate::ClientOptions::default()).unwrap();
+    let subject = other.uuid();
+    let property = Property::new("value", None);
+    other.property_set(subject, "key", &property).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(cache.property(subject, "key"), Some(property));
+    assert_eq!(
+        changes.try_recv(),
+        Ok(PropertyChangeOwned::Created {
+            subject,
+            key: "key".to_string()
+        })
+    );
+
+    cache.resolve_clients(client.as_client());
+    drop(other);
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(cache.subject(subject), None);
+    assert!(!cache.subjects().contains(&subject));
+    drop(client);
+}
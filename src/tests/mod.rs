@@ -5,11 +5,15 @@ use ctor::ctor;
 use crate::{Client, ClientOptions};
 
 mod client;
+mod graph;
 mod log;
+mod metadata;
 mod processing;
 mod ringbuffer;
+mod server;
 mod time;
 mod transport;
+mod uuid;
 
 pub static DEFAULT_TEST_CLIENT: LazyLock<Client> = LazyLock::new(|| {
     Client::new("default-test-client", ClientOptions::default())
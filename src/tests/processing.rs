@@ -193,9 +193,325 @@ fn messages_in_midi_ports_are_forwarded() {
     ac.deactivate().unwrap();
 }
 
+#[test]
+fn midi_messages_roundtrip_through_ports() {
+    use std::convert::TryFrom;
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::MidiIn::default())
+        .unwrap();
+    let mut output = client
+        .register_port("out", crate::MidiOut::default())
+        .unwrap();
+    let (input_name, output_name) = (input.name().unwrap(), output.name().unwrap());
+    let messages = [
+        crate::MidiMessage::NoteOn {
+            channel: 3,
+            note: 60,
+            velocity: 100,
+        },
+        crate::MidiMessage::PitchBend {
+            channel: 15,
+            value: 12345,
+        },
+        crate::MidiMessage::SysEx(&[0xF0, 0x7D, 0x01, 0xF7]),
+        crate::MidiMessage::Clock,
+    ];
+    let (send, recv) = std::sync::mpsc::sync_channel(1);
+    let process_handler = crate::contrib::ClosureProcessHandler::new(move |_, ps| {
+        let mut writer = output.writer(ps);
+        for (time, message) in messages.iter().enumerate() {
+            writer
+                .write_message(time as crate::Frames, message)
+                .unwrap();
+        }
+
+        let received: Vec<_> = input
+            .iter(ps)
+            .map(|raw| crate::MidiMessage::try_from(raw).unwrap())
+            .collect();
+        if received.len() == messages.len() {
+            assert_eq!(received, messages);
+            send.try_send(true).unwrap();
+            crate::Control::Quit
+        } else {
+            crate::Control::Continue
+        }
+    });
+    let ac = client.activate_async((), process_handler).unwrap();
+    ac.as_client()
+        .connect_ports_by_name(&output_name, &input_name)
+        .unwrap();
+    assert!(recv
+        .recv_timeout(std::time::Duration::from_secs(1))
+        .unwrap());
+    ac.deactivate().unwrap();
+}
+
+#[test]
+fn reserved_midi_events_are_forwarded() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::MidiIn::default())
+        .unwrap();
+    let mut output = client
+        .register_port("out", crate::MidiOut::default())
+        .unwrap();
+    let (input_name, output_name) = (input.name().unwrap(), output.name().unwrap());
+    let mut sysex = vec![0x42; 512];
+    sysex[0] = 0xF0;
+    sysex[511] = 0xF7;
+    let (send, recv) = std::sync::mpsc::sync_channel(1);
+    let process_handler = crate::contrib::ClosureProcessHandler::new(move |_, ps| {
+        let mut writer = output.writer(ps);
+        writer
+            .reserve(1, sysex.len())
+            .unwrap()
+            .copy_from_slice(&sysex);
+        assert!(matches!(
+            writer.reserve(0, 1),
+            Err(crate::Error::NotEnoughSpace)
+        ));
+
+        let received: Vec<_> = input.iter(ps).collect();
+        if received.len() == 1 {
+            assert_eq!(received[0].time, 1);
+            assert_eq!(received[0].bytes, &sysex[..]);
+            send.try_send(true).unwrap();
+            crate::Control::Quit
+        } else {
+            crate::Control::Continue
+        }
+    });
+    let ac = client.activate_async((), process_handler).unwrap();
+    ac.as_client()
+        .connect_ports_by_name(&output_name, &input_name)
+        .unwrap();
+    assert!(recv
+        .recv_timeout(std::time::Duration::from_secs(1))
+        .unwrap());
+    ac.deactivate().unwrap();
+}
+
+#[test]
+fn midi_scheduler_writes_events_at_their_frame() {
+    use crate::contrib::midi_scheduler::{self, Timestamp};
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::MidiIn::default())
+        .unwrap();
+    let mut output = client
+        .register_port("out", crate::MidiOut::default())
+        .unwrap();
+    let (input_name, output_name) = (input.name().unwrap(), output.name().unwrap());
+    let (mut scheduler, mut scheduler_output) = midi_scheduler::channel(1024).unwrap();
+    let (send, recv) = std::sync::mpsc::sync_channel(4);
+    let process_handler = crate::contrib::ClosureProcessHandler::new(move |_, ps| {
+        let mut writer = output.writer(ps);
+        scheduler_output.write_due(ps, &mut writer).unwrap();
+        for event in input.iter(ps) {
+            send.try_send(ps.last_frame_time().wrapping_add(event.time))
+                .unwrap();
+        }
+        crate::Control::Continue
+    });
+    let ac = client.activate_async((), process_handler).unwrap();
+    ac.as_client()
+        .connect_ports_by_name(&output_name, &input_name)
+        .unwrap();
+
+    let message = crate::MidiMessage::Clock;
+    let late_frame = ac.as_client().frame_time().wrapping_sub(1);
+    scheduler
+        .schedule(Timestamp::Frame(late_frame), &message)
+        .unwrap();
+    let frame = ac.as_client().frame_time() + 4 * ac.as_client().buffer_size() + 7;
+    scheduler
+        .schedule(Timestamp::Frame(frame), &message)
+        .unwrap();
+
+    let timeout = std::time::Duration::from_secs(1);
+    let late_played_at = recv.recv_timeout(timeout).unwrap();
+    assert!(late_played_at.wrapping_sub(late_frame) as i32 > 0);
+    assert_eq!(recv.recv_timeout(timeout).unwrap(), frame);
+    assert_eq!(scheduler.late_events(), 1);
+    assert_eq!(scheduler.dropped_events(), 0);
+    ac.deactivate().unwrap();
+}
+
+#[test]
+fn smf_recorder_records_events_in_order() {
+    use crate::contrib::smf::{record, SmfEventData, SmfFormat, DEFAULT_TEMPO};
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let input = client
+        .register_port("in", crate::MidiIn::default())
+        .unwrap();
+    let mut output = client
+        .register_port("out", crate::MidiOut::default())
+        .unwrap();
+    let (input_name, output_name) = (input.name().unwrap(), output.name().unwrap());
+    let (mut recorder, mut recorder_input) = record::channel(1 << 16).unwrap();
+    let process_handler = crate::contrib::ClosureProcessHandler::new(move |_, ps| {
+        let mut writer = output.writer(ps);
+        writer.write_message(0, &crate::MidiMessage::Start).unwrap();
+        recorder_input.record(ps, &input);
+        crate::Control::Continue
+    });
+    let ac = client.activate_async((), process_handler).unwrap();
+    ac.as_client()
+        .connect_ports_by_name(&output_name, &input_name)
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let sample_rate = ac.as_client().sample_rate();
+    ac.deactivate().unwrap();
+
+    recorder.collect();
+    assert!(!recorder.is_empty());
+    assert_eq!(recorder.dropped_events(), 0);
+    let smf = recorder.to_smf(SmfFormat::MultiTrack, sample_rate, 960, DEFAULT_TEMPO);
+    assert_eq!(smf.tracks.len(), 2);
+    assert_eq!(smf.tracks[0][0].data, SmfEventData::Tempo(DEFAULT_TEMPO));
+    assert!(smf.tracks[1]
+        .windows(2)
+        .all(|events| events[0].tick < events[1].tick));
+    assert!(smf.tracks[1]
+        .iter()
+        .all(|event| event.data == SmfEventData::Midi(vec![0xFA])));
+}
+
 #[test]
 fn activating_client_notifies_buffer_size_before_beginning() {
     let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
     let initial_buffer_size = client.buffer_size() as usize;
     assert_ne!(initial_buffer_size, 0);
 }
+
+struct LatencyRecorder(std::sync::mpsc::SyncSender<crate::LatencyType>);
+
+impl crate::NotificationHandler for LatencyRecorder {
+    const LATENCY: bool = true;
+
+    fn latency(&mut self, _: &crate::Client, mode: crate::LatencyType) {
+        self.0.try_send(mode).ok();
+    }
+}
+
+#[test]
+fn recomputing_latencies_calls_latency_for_both_modes() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let (send, recv) = std::sync::mpsc::sync_channel(16);
+    let ac = client.activate_async(LatencyRecorder(send), ()).unwrap();
+    // Drain notifications caused by activation.
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    while recv.try_recv().is_ok() {}
+
+    ac.as_client().recompute_total_latencies().unwrap();
+    let timeout = std::time::Duration::from_secs(1);
+    let modes = [
+        recv.recv_timeout(timeout).unwrap(),
+        recv.recv_timeout(timeout).unwrap(),
+    ];
+    assert!(modes.contains(&crate::LatencyType::Capture));
+    assert!(modes.contains(&crate::LatencyType::Playback));
+    ac.deactivate().unwrap();
+}
+
+#[test]
+fn freewheel_render_processes_at_least_target_frames() {
+    use crate::contrib::freewheel::{self, RenderTarget};
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let target = 10 * u64::from(client.sample_rate());
+    let handler = crate::contrib::ClosureProcessHandler::new(|_, _| crate::Control::Continue);
+    let (handler, renderer) = freewheel::wrap(handler);
+    let ac = client.activate_async((), handler).unwrap();
+    let start = std::time::Instant::now();
+    let frames = renderer
+        .render(ac.as_client(), RenderTarget::Frames(target))
+        .unwrap();
+    assert!(frames >= target, "{} < {}", frames, target);
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    ac.deactivate().unwrap();
+}
+
+#[test]
+fn freewheel_render_fails_instead_of_waiting_forever() {
+    use crate::contrib::freewheel::{self, RenderTarget};
+    use std::time::Duration;
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let handler = crate::contrib::ClosureProcessHandler::new(|_, _| crate::Control::Quit);
+    let (handler, renderer) = freewheel::wrap(handler);
+    let renderer = renderer.with_timeout(Duration::from_millis(200));
+    let ac = client.activate_async((), handler).unwrap();
+    ac.as_client().transport().stop().unwrap();
+    assert_eq!(
+        renderer.render(ac.as_client(), RenderTarget::TransportFrame(u32::MAX)),
+        Err(crate::Error::TransportNotRolling)
+    );
+    assert_eq!(
+        renderer.render(ac.as_client(), RenderTarget::Frames(u64::MAX)),
+        Err(crate::Error::FreewheelTimeout)
+    );
+}
+
+#[test]
+fn process_thread_handler_runs_cycles() {
+    struct CycleCounter {
+        cycles: usize,
+        send: std::sync::mpsc::SyncSender<usize>,
+    }
+
+    impl crate::ProcessThreadHandler for CycleCounter {
+        fn run(&mut self, _: &crate::Client, cycles: &mut crate::ProcessCycles) {
+            while let Some(cycle) = cycles.wait() {
+                assert!(cycle.n_frames() > 0);
+                self.cycles += 1;
+                self.send.try_send(self.cycles).ok();
+                cycle.signal(crate::Control::Continue);
+            }
+        }
+    }
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let (send, recv) = std::sync::mpsc::sync_channel(2);
+    let ac = client
+        .activate_process_thread((), CycleCounter { cycles: 0, send })
+        .unwrap();
+    assert_eq!(recv.recv_timeout(std::time::Duration::from_secs(1)), Ok(1));
+    assert_eq!(recv.recv_timeout(std::time::Duration::from_secs(1)), Ok(2));
+    let (_, _, handler) = ac.deactivate().unwrap();
+    assert!(handler.into_inner().cycles >= 2);
+}
+
+#[test]
+fn process_thread_handler_returns_after_quitting() {
+    struct Quitter {
+        send: std::sync::mpsc::SyncSender<Vec<usize>>,
+    }
+
+    impl crate::ProcessThreadHandler for Quitter {
+        fn run(&mut self, _: &crate::Client, cycles: &mut crate::ProcessCycles) {
+            // Dropped when `run` returns, which is only sound if JACK does not end the thread.
+            let mut seen = Vec::new();
+            while let Some(cycle) = cycles.wait() {
+                seen.push(cycle.n_frames() as usize);
+                cycle.signal(crate::Control::Quit);
+            }
+            self.send.try_send(seen).ok();
+        }
+    }
+
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let (send, recv) = std::sync::mpsc::sync_channel(1);
+    let _ac = client
+        .activate_process_thread((), Quitter { send })
+        .unwrap();
+    let seen = recv
+        .recv_timeout(std::time::Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(seen.len(), 1);
+}
@@ -0,0 +1,53 @@
+use crate::server::{ParameterType, ParameterValidationError, ParameterValue, Server, ServerState};
+use crate::Error;
+
+#[test]
+fn server_lists_dummy_driver() {
+    let server = match Server::new() {
+        Ok(server) => server,
+        Err(Error::WeakFunctionNotFound(f)) => {
+            eprintln!("jackctl is not supported by the JACK library, missing {f}");
+            return;
+        }
+        Err(err) => panic!("{}", err),
+    };
+    assert_eq!(server.state(), ServerState::Created);
+    let driver = server.driver_by_name("dummy").unwrap();
+    assert_eq!(driver.name().unwrap(), "dummy");
+    assert_eq!(
+        server.driver_by_name("not-a-driver").err(),
+        Some(Error::DriverNotFound("not-a-driver".to_string()))
+    );
+}
+
+#[test]
+fn server_can_not_start_before_open() {
+    let Ok(server) = Server::new() else {
+        return;
+    };
+    assert_eq!(server.start(), Err(Error::ServerStartError));
+    assert_eq!(server.stop(), Err(Error::ServerStopError));
+    assert_eq!(server.state(), ServerState::Created);
+}
+
+#[test]
+fn dummy_driver_parameters_are_typed() {
+    let Ok(server) = Server::new() else {
+        return;
+    };
+    let driver = server.driver_by_name("dummy").unwrap();
+    let rate = driver.parameter_by_name("rate").unwrap().unwrap();
+    assert_eq!(rate.parameter_type().unwrap(), ParameterType::UInt);
+    rate.set_value(&ParameterValue::UInt(48000)).unwrap();
+    assert_eq!(rate.value().unwrap(), ParameterValue::UInt(48000));
+    assert!(rate.is_set().unwrap());
+    assert!(matches!(
+        rate.set_value(&ParameterValue::String("48000".into())),
+        Err(Error::InvalidParameterValue {
+            reason: ParameterValidationError::TypeMismatch { .. },
+            ..
+        })
+    ));
+    rate.reset().unwrap();
+    assert_eq!(rate.value().unwrap(), rate.default_value().unwrap());
+}
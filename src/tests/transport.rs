@@ -11,6 +11,11 @@ fn new_transport_is_not_valid() {
     assert_eq!(TransportPosition::default().bbt_offset(), None);
     assert_eq!(TransportPosition::default().frame_rate(), None);
     assert_eq!(TransportPosition::default().usecs(), None);
+    assert_eq!(
+        TransportPosition::default().audio_frames_per_video_frame(),
+        None
+    );
+    assert_eq!(TransportPosition::default().video_offset(), None);
 }
 
 #[test]
@@ -28,3 +33,105 @@ fn starting_transport_sets_state_to_started() {
 
     transport.stop().unwrap();
 }
+
+struct FixedTempo;
+
+impl crate::TimebaseHandler for FixedTempo {
+    fn timebase(
+        &mut self,
+        _: &Client,
+        _state: TransportState,
+        _n_frames: crate::Frames,
+        pos: &mut TransportPosition,
+        _is_new_position: bool,
+    ) {
+        let bbt = crate::TransportBBT::default()
+            .with_bpm(93.0)
+            .with_timesig(7.0, 8.0)
+            .validated()
+            .unwrap();
+        pos.set_bbt(Some(bbt)).unwrap();
+        pos.set_audio_frames_per_video_frame(Some(1920.0)).unwrap();
+        pos.set_video_offset(Some(7)).unwrap();
+    }
+}
+
+#[test]
+fn video_fields_can_be_set_and_cleared() {
+    let mut pos = TransportPosition::default();
+    pos.set_audio_frames_per_video_frame(Some(1601.6)).unwrap();
+    pos.set_video_offset(Some(100)).unwrap();
+    assert_eq!(pos.audio_frames_per_video_frame(), Some(1601.6));
+    assert_eq!(pos.video_offset(), Some(100));
+    assert_eq!(pos.set_audio_frames_per_video_frame(Some(0.0)), Err(0.0));
+    assert_eq!(pos.audio_frames_per_video_frame(), Some(1601.6));
+
+    pos.set_audio_frames_per_video_frame(None).unwrap();
+    pos.set_video_offset(None).unwrap();
+    assert_eq!(pos.audio_frames_per_video_frame(), None);
+    assert_eq!(pos.video_offset(), None);
+}
+
+#[test]
+fn timebase_master_publishes_bbt() {
+    let (client, _) = Client::new("", Default::default()).unwrap();
+    let mut ac = client.activate_async((), ()).unwrap();
+    ac.set_timebase_handler(FixedTempo).unwrap();
+    sleep(Duration::from_millis(100));
+    let bbt = ac
+        .as_client()
+        .transport()
+        .query()
+        .unwrap()
+        .pos
+        .bbt()
+        .unwrap();
+    assert_eq!(bbt.bpm, 93.0);
+    assert_eq!((bbt.sig_num, bbt.sig_denom), (7.0, 8.0));
+    let pos = ac.as_client().transport().query().unwrap().pos;
+    assert_eq!(pos.audio_frames_per_video_frame(), Some(1920.0));
+    assert_eq!(pos.video_offset(), Some(7));
+
+    ac.release_timebase().unwrap();
+    assert_eq!(ac.release_timebase(), Err(crate::Error::NotTimebaseMaster));
+    ac.deactivate().unwrap();
+}
+
+struct Tempo(f64);
+
+impl crate::TimebaseHandler for Tempo {
+    fn timebase(
+        &mut self,
+        _: &Client,
+        _state: TransportState,
+        _n_frames: crate::Frames,
+        pos: &mut TransportPosition,
+        _is_new_position: bool,
+    ) {
+        let bbt = crate::TransportBBT::default()
+            .with_bpm(self.0)
+            .validated()
+            .unwrap();
+        pos.set_bbt(Some(bbt)).unwrap();
+    }
+}
+
+#[test]
+fn timebase_handler_can_be_replaced_by_another_type() {
+    let (client, _) = Client::new("", Default::default()).unwrap();
+    let mut ac = client.activate_async((), ()).unwrap();
+    let bpm = |ac: &crate::AsyncClient<(), ()>| {
+        let pos = ac.as_client().transport().query().unwrap().pos;
+        pos.bbt().unwrap().bpm
+    };
+    ac.set_timebase_handler(FixedTempo).unwrap();
+    sleep(Duration::from_millis(100));
+    assert_eq!(bpm(&ac), 93.0);
+    ac.set_timebase_handler(Tempo(140.0)).unwrap();
+    sleep(Duration::from_millis(100));
+    assert_eq!(bpm(&ac), 140.0);
+    ac.set_timebase_handler(FixedTempo).unwrap();
+    sleep(Duration::from_millis(100));
+    assert_eq!(bpm(&ac), 93.0);
+    ac.deactivate().unwrap();
+}
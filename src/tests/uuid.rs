@@ -0,0 +1,53 @@
+use crate::Uuid;
+
+#[test]
+fn uuid_roundtrips_through_string() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let uuid = client.uuid();
+    assert_eq!(uuid.to_string(), client.uuid_string());
+    assert_eq!(uuid.to_string().parse::<Uuid>(), Ok(uuid));
+}
+
+#[test]
+fn invalid_uuid_string_fails_to_parse() {
+    assert_eq!(
+        Uuid::parse("not a uuid"),
+        Err(crate::Error::InvalidUuid("not a uuid".to_string()))
+    );
+}
+
+#[test]
+fn empty_uuid_is_empty() {
+    assert!(Uuid::EMPTY.is_empty());
+    assert!(Uuid::default().is_empty());
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    assert!(!client.uuid().is_empty());
+}
+
+#[test]
+fn ports_have_distinct_uuids() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let a = client
+        .register_port("a", crate::AudioIn::default())
+        .unwrap();
+    let b = client
+        .register_port("b", crate::AudioIn::default())
+        .unwrap();
+    let (a, b) = (a.uuid().unwrap(), b.uuid().unwrap());
+    assert!(!a.is_empty());
+    assert_ne!(a, b);
+    assert_ne!(a.to_index(), b.to_index());
+}
+
+#[test]
+fn properties_can_be_set_on_ports() {
+    let (client, _) = crate::Client::new("", crate::ClientOptions::default()).unwrap();
+    let port = client
+        .register_port("p", crate::AudioOut::default())
+        .unwrap();
+    let uuid = port.uuid().unwrap();
+    let prop = crate::Property::new("Left", None);
+    let key = "http://jackaudio.org/metadata/pretty-name";
+    assert_eq!(client.property_set(uuid, key, &prop), Ok(()));
+    assert_eq!(client.property_get(uuid, key), Some(prop));
+}
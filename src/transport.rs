@@ -230,6 +230,16 @@ impl TransportPosition {
         (self.0.valid & j::JackBBTFrameOffset) != 0
     }
 
+    /// Query to see if the number of audio frames per video frame is valid.
+    pub fn valid_audio_video_ratio(&self) -> bool {
+        (self.0.valid & j::JackAudioVideoRatio) != 0
+    }
+
+    /// Query to see if the video frame offset is valid.
+    pub fn valid_video_frame_offset(&self) -> bool {
+        (self.0.valid & j::JackVideoFrameOffset) != 0
+    }
+
     /// Get the frame number on the transport timeline.
     ///
     /// # Remarks
@@ -364,6 +374,80 @@ impl TransportPosition {
             }
         }
     }
+
+    /// Get the number of audio frames per video frame.
+    ///
+    /// # Remarks
+    ///
+    /// * This is only set by a timebase master that follows a video frame rate.
+    /// * It may be fractional, such as 1601.6 for 29.97 fps at 48 kHz.
+    pub fn audio_frames_per_video_frame(&self) -> Option<f32> {
+        if self.valid_audio_video_ratio() {
+            Some(self.0.audio_frames_per_video_frame)
+        } else {
+            None
+        }
+    }
+
+    /// Set the number of audio frames per video frame.
+    ///
+    /// # Arguments
+    /// * `ratio` - The number of audio frames per video frame. `None` will invalidate the ratio.
+    ///
+    /// # Remarks
+    /// * If `ratio` is not a positive number, will leave the pre-existing data intact and return
+    ///   it as the error.
+    pub fn set_audio_frames_per_video_frame(
+        &mut self,
+        ratio: Option<f32>,
+    ) -> std::result::Result<(), f32> {
+        match ratio {
+            None => {
+                self.0.valid &= !j::JackAudioVideoRatio;
+                Ok(())
+            }
+            Some(ratio) if ratio.is_finite() && ratio > 0.0 => {
+                self.0.audio_frames_per_video_frame = ratio;
+                self.0.valid |= j::JackAudioVideoRatio;
+                Ok(())
+            }
+            Some(ratio) => Err(ratio),
+        }
+    }
+
+    /// Get the video frame offset.
+    ///
+    /// # Remarks
+    ///
+    /// * This is the number of frames from the start of the cycle to the first video frame that
+    ///   starts in it.
+    /// * Only valid together with `audio_frames_per_video_frame`.
+    pub fn video_offset(&self) -> Option<Frames> {
+        if self.valid_video_frame_offset() {
+            Some(self.0.video_offset)
+        } else {
+            None
+        }
+    }
+
+    /// Set the video frame offset.
+    ///
+    /// # Arguments
+    /// * `frame` - The number of frames from the start of the cycle to the first video frame that
+    ///   starts in it. `None` will invalidate the offset data.
+    pub fn set_video_offset(&mut self, frame: Option<Frames>) -> std::result::Result<(), Frames> {
+        match frame {
+            None => {
+                self.0.valid &= !j::JackVideoFrameOffset;
+                Ok(())
+            }
+            Some(frame) => {
+                self.0.video_offset = frame;
+                self.0.valid |= j::JackVideoFrameOffset;
+                Ok(())
+            }
+        }
+    }
 }
 
 impl std::fmt::Debug for TransportPosition {
@@ -383,6 +467,12 @@ impl std::fmt::Debug for TransportPosition {
         if let Some(bbt) = self.bbt() {
             d = d.field("bbt", &bbt);
         }
+        if let Some(ratio) = self.audio_frames_per_video_frame() {
+            d = d.field("audio_frames_per_video_frame", &ratio);
+        }
+        if let Some(video_offset) = self.video_offset() {
+            d = d.field("video_offset", &video_offset);
+        }
         d.finish()
     }
 }
@@ -457,6 +547,20 @@ impl TransportBBT {
     pub fn valid(&self) -> bool {
         self.validated().is_ok()
     }
+
+    /// The position in quarter notes since the start of the song, assuming that the time signature
+    /// never changed.
+    pub(crate) fn quarter_notes(&self) -> f64 {
+        let beats = (self.bar - 1) as f64 * f64::from(self.sig_num)
+            + (self.beat - 1) as f64
+            + self.tick as f64 / self.ticks_per_beat;
+        beats * 4.0 / f64::from(self.sig_denom)
+    }
+
+    /// The number of quarter notes that pass in `n_frames` at the current tempo.
+    pub(crate) fn quarter_notes_in(&self, n_frames: Frames, sample_rate: f64) -> f64 {
+        f64::from(n_frames) / sample_rate * self.bpm / 60.0 * 4.0 / f64::from(self.sig_denom)
+    }
 }
 
 impl Default for TransportPosition {
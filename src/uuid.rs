@@ -0,0 +1,81 @@
+use jack_sys as j;
+use std::{ffi, fmt, str};
+
+use crate::Error;
+
+/// The length of the string representation of a `Uuid`, not including the final `NULL` character.
+const UUID_STRING_SIZE: usize = 36;
+
+/// A unique identifier for a JACK client or port. Uuids are the subjects of metadata, see
+/// [`Client::property_set`](crate::Client::property_set).
+///
+/// # Example
+/// ```no_run
+/// let (client, _status) =
+///     jack::Client::new("rusty_client", jack::ClientOptions::default()).unwrap();
+/// let uuid = client.uuid();
+/// let parsed: jack::Uuid = uuid.to_string().parse().unwrap();
+/// assert_eq!(uuid, parsed);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Uuid(j::jack_uuid_t);
+
+impl Uuid {
+    /// The empty `Uuid`, which does not identify any client or port.
+    pub const EMPTY: Uuid = Uuid(0);
+
+    /// Returns `true` if this is the empty `Uuid`.
+    pub fn is_empty(self) -> bool {
+        unsafe { j::jack_uuid_empty(self.0) != 0 }
+    }
+
+    /// The index of the client or port within the JACK server. Only unique among subjects of the
+    /// same kind.
+    pub fn to_index(self) -> u32 {
+        unsafe { j::jack_uuid_to_index(self.0) }
+    }
+
+    /// Parse a `Uuid` from its string representation, as produced by `Display`.
+    ///
+    /// `Err(Error::InvalidUuid)` is returned if `s` is not a valid `Uuid`.
+    pub fn parse(s: &str) -> Result<Uuid, Error> {
+        let cstr = ffi::CString::new(s).map_err(|_| Error::InvalidUuid(s.to_string()))?;
+        let mut uuid: j::jack_uuid_t = 0;
+        match unsafe { j::jack_uuid_parse(cstr.as_ptr(), &mut uuid) } {
+            0 => Ok(Uuid(uuid)),
+            _ => Err(Error::InvalidUuid(s.to_string())),
+        }
+    }
+
+    /// Create a `Uuid` from its raw value.
+    pub fn from_raw(uuid: j::jack_uuid_t) -> Uuid {
+        Uuid(uuid)
+    }
+
+    /// Get the raw value of the `Uuid`.
+    ///
+    /// This is mostly for use within the jack crate itself.
+    pub fn raw(self) -> j::jack_uuid_t {
+        self.0
+    }
+}
+
+impl str::FromStr for Uuid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Uuid, Error> {
+        Uuid::parse(s)
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = [0 as libc::c_char; UUID_STRING_SIZE + 1];
+        let s = unsafe {
+            j::jack_uuid_unparse(self.0, buf.as_mut_ptr());
+            ffi::CStr::from_ptr(buf.as_ptr())
+        };
+        f.write_str(&s.to_string_lossy())
+    }
+}